@@ -0,0 +1,709 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Betting syndicate vault: members pool funds in exchange for shares, a manager bets the pool on
+//! the roulette through the `RouletteExtension` of the runtime, and members withdraw their share
+//! of the vault, winnings included. The manager can also set a bet that the vault places again
+//! every time the runtime calls it back with the settlement of the previous one.
+//!
+//! Shares are priced on the balance of the vault, which does not hold the stakes in play, so
+//! deposits and withdrawals wait until every bet of the vault is settled. The runtime may credit
+//! winnings instead of paying them, so the vault claims them into its balance before pricing
+//! shares and whenever one of its bets wins.
+
+use ink_env::{DefaultEnvironment, Environment};
+use ink_lang as ink;
+use ink_prelude::vec::Vec;
+#[cfg(feature = "std")]
+use ink_storage::traits::StorageLayout;
+use ink_storage::traits::{PackedLayout, SpreadLayout};
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+
+/// Colors of the roulette pockets, encoded as the runtime encodes them.
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub enum RouletteColor {
+	Red,
+	Black,
+}
+
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub enum DozenOrColumn {
+	First,
+	Second,
+	Third,
+}
+
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub enum Half {
+	First,
+	Second,
+}
+
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub enum OddOrEven {
+	Odd,
+	Even,
+}
+
+/// Roulette bet, encoded as the runtime encodes it.
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub enum Bet {
+	Color(RouletteColor),
+	Full(u32),
+	Dozen(DozenOrColumn),
+	Column(DozenOrColumn),
+	Half(Half),
+	OddOrEven(OddOrEven),
+}
+
+/// Bet as stored by the runtime.
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct BetData {
+	pub id: u64,
+	pub owner: AccountId,
+	pub asset: u32,
+	pub amount: Balance,
+	pub block: BlockNumber,
+	pub bet: Bet,
+	pub voucher: Option<u64>,
+	pub multiple: u32,
+}
+
+/// Summary of a played bet, as stored by the runtime.
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct SettledBet {
+	pub round: u32,
+	pub won: bool,
+	pub payout: Balance,
+	pub record_hash: [u8; 32],
+}
+
+/// How the runtime derived the outcome of a spin.
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Derivation {
+	Blake2Concat,
+	Blake2Uniform,
+}
+
+/// Spin that played a round, as stored by the runtime.
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct SpinRecord {
+	pub seed: [u8; 32],
+	pub subject: Vec<u8>,
+	pub derivation: Derivation,
+	pub block: BlockNumber,
+	pub outcome: u32,
+}
+
+/// Settlement of a bet, as the runtime hands it to the callback of the contract.
+#[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct BetSettlement {
+	pub bet_id: u64,
+	pub round: u32,
+	pub outcome: u32,
+	pub won: bool,
+	pub payout: Balance,
+}
+
+/// Roulette functions of the runtime.
+#[ink::chain_extension]
+pub trait RouletteExtension {
+	type ErrorCode = RouletteError;
+
+	/// Places a bet of the contract and returns its id.
+	#[ink(extension = 1, returns_result = false)]
+	fn place_bet(asset: u32, amount: Balance, bet: Bet) -> u64;
+
+	/// Bets of the contract waiting to be played, with the round that takes them.
+	#[ink(extension = 2, returns_result = false, handle_status = false)]
+	fn ongoing_bets() -> Vec<(u32, BetData)>;
+
+	/// Summary of a bet that was already played.
+	#[ink(extension = 3, returns_result = false, handle_status = false)]
+	fn settled_bet(bet_id: u64) -> Option<SettledBet>;
+
+	/// Spin that played a round.
+	#[ink(extension = 4, returns_result = false, handle_status = false)]
+	fn spin(round: u32) -> Option<SpinRecord>;
+
+	/// Places a bet of the contract, asking the runtime to call `selector` back with its
+	/// settlement, and returns its id.
+	#[ink(extension = 5, returns_result = false)]
+	fn place_bet_with_callback(asset: u32, amount: Balance, bet: Bet, selector: [u8; 4]) -> u64;
+
+	/// Pays the winnings credited to the contract and returns the amount paid.
+	#[ink(extension = 6, returns_result = false)]
+	fn claim_winnings(asset: u32) -> Balance;
+}
+
+/// Why the runtime did not take a bet or pay winnings.
+#[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RouletteError {
+	/// The contract cannot afford the bet.
+	NotEnoughBalance,
+	/// The house cannot cover the bet.
+	NotEnoughHouseBalance,
+	/// The bet cannot be played.
+	InvalidBet,
+	/// The bet failed for any other reason.
+	BetFailed,
+	/// No winnings are credited to the contract.
+	NothingToClaim,
+}
+
+impl ink_env::chain_extension::FromStatusCode for RouletteError {
+	fn from_status_code(status_code: u32) -> Result<(), Self> {
+		match status_code {
+			0 => Ok(()),
+			1 => Err(Self::NotEnoughBalance),
+			2 => Err(Self::NotEnoughHouseBalance),
+			3 => Err(Self::InvalidBet),
+			5 => Err(Self::NothingToClaim),
+			_ => Err(Self::BetFailed),
+		}
+	}
+}
+
+/// Default environment with the roulette chain extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RouletteEnvironment {}
+
+impl Environment for RouletteEnvironment {
+	const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+	type AccountId = AccountId;
+	type Balance = Balance;
+	type Hash = <DefaultEnvironment as Environment>::Hash;
+	type BlockNumber = BlockNumber;
+	type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+	type ChainExtension = RouletteExtension;
+}
+
+#[ink::contract(env = crate::RouletteEnvironment)]
+mod syndicate {
+	use super::{Bet, BetData, BetSettlement, RouletteError, SettledBet, SpinRecord};
+	use ink_prelude::vec::Vec;
+	use ink_storage::{traits::SpreadAllocate, Mapping};
+
+	/// Vault shared by the members of the syndicate.
+	#[ink(storage)]
+	#[derive(SpreadAllocate)]
+	pub struct Syndicate {
+		/// Account that bets the vault.
+		manager: AccountId,
+		/// Asset id of the native currency in the roulette.
+		asset: u32,
+		/// Shares of every member.
+		shares: Mapping<AccountId, Balance>,
+		/// Shares of all the members.
+		total_shares: Balance,
+		/// Bet placed again on every settlement, if any.
+		auto_bet: Option<(Balance, Bet)>,
+		/// Bets of the vault that were not seen settled yet.
+		unsettled: Vec<u64>,
+	}
+
+	/// Bets that the vault can have in play at once.
+	pub const MAX_UNSETTLED_BETS: usize = 32;
+
+	/// Selector of `on_bet_settled`, which the runtime calls back.
+	pub const ON_BET_SETTLED: [u8; 4] = [0x5e, 0x77, 0x1e, 0xd0];
+
+	#[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+	#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+	pub enum Error {
+		/// Only the manager can bet the vault.
+		NotManager,
+		/// Only the runtime calls back the vault, on its behalf.
+		NotCallback,
+		/// Deposits must transfer some value.
+		NothingDeposited,
+		/// The member does not own that many shares.
+		NotEnoughShares,
+		/// The withdrawal could not be transferred.
+		TransferFailed,
+		/// Shares cannot be priced while bets of the vault are in play.
+		BetsInPlay,
+		/// The vault already has `MAX_UNSETTLED_BETS` bets in play.
+		TooManyBetsInPlay,
+		/// The roulette did not take the bet.
+		Roulette(RouletteError),
+	}
+
+	impl From<RouletteError> for Error {
+		fn from(error: RouletteError) -> Self {
+			Error::Roulette(error)
+		}
+	}
+
+	/// A member joined the vault or added to its stake.
+	#[ink(event)]
+	pub struct Deposited {
+		#[ink(topic)]
+		member: AccountId,
+		amount: Balance,
+		shares: Balance,
+	}
+
+	/// A member took its share of the vault out.
+	#[ink(event)]
+	pub struct Withdrawn {
+		#[ink(topic)]
+		member: AccountId,
+		amount: Balance,
+		shares: Balance,
+	}
+
+	/// The manager bet part of the vault.
+	#[ink(event)]
+	pub struct BetPlaced {
+		bet_id: u64,
+		amount: Balance,
+		bet: Bet,
+	}
+
+	/// A bet of the vault was settled.
+	#[ink(event)]
+	pub struct BetSettled {
+		bet_id: u64,
+		won: bool,
+		payout: Balance,
+	}
+
+	impl Syndicate {
+		/// Creates a vault managed by the caller, that bets in the native asset `asset`.
+		#[ink(constructor)]
+		pub fn new(asset: u32) -> Self {
+			ink_lang::utils::initialize_contract(|contract: &mut Self| {
+				contract.manager = Self::env().caller();
+				contract.asset = asset;
+			})
+		}
+
+		/// Adds the transferred value to the vault, in exchange for shares priced on what the
+		/// vault holds. Fails, sending the value back, while bets of the vault are in play.
+		#[ink(message, payable)]
+		pub fn deposit(&mut self) -> Result<Balance, Error> {
+			let member = self.env().caller();
+			let amount = self.env().transferred_value();
+			if amount == 0 {
+				return Err(Error::NothingDeposited)
+			}
+			if let Err(error) = self.settle_vault() {
+				// Errors do not revert the call, so the value is sent back.
+				self.env().transfer(member, amount).map_err(|_| Error::TransferFailed)?;
+				return Err(error)
+			}
+
+			let vault = self.env().balance().saturating_sub(amount);
+			let shares = match self.total_shares {
+				0 => amount,
+				_ if vault == 0 => amount,
+				total_shares => amount.saturating_mul(total_shares) / vault,
+			};
+			let owned = self.shares.get(member).unwrap_or_default();
+			self.shares.insert(member, &owned.saturating_add(shares));
+			self.total_shares = self.total_shares.saturating_add(shares);
+
+			self.env().emit_event(Deposited { member, amount, shares });
+			Ok(shares)
+		}
+
+		/// Burns `shares` of the caller and transfers their part of the vault back. Fails while
+		/// bets of the vault are in play, and keeps the shares when the transfer fails.
+		#[ink(message)]
+		pub fn withdraw(&mut self, shares: Balance) -> Result<Balance, Error> {
+			let member = self.env().caller();
+			let owned = self.shares.get(member).unwrap_or_default();
+			if shares == 0 || shares > owned {
+				return Err(Error::NotEnoughShares)
+			}
+			self.settle_vault()?;
+
+			// Errors do not revert the call, so the shares are only burnt once they are paid.
+			let amount = self.env().balance().saturating_mul(shares) / self.total_shares;
+			self.env().transfer(member, amount).map_err(|_| Error::TransferFailed)?;
+			self.shares.insert(member, &(owned - shares));
+			self.total_shares -= shares;
+
+			self.env().emit_event(Withdrawn { member, amount, shares });
+			Ok(amount)
+		}
+
+		/// Bets `amount` of the vault on `bet`. Winnings are claimed into the vault before shares
+		/// are priced again.
+		#[ink(message)]
+		pub fn bet(&mut self, amount: Balance, bet: Bet) -> Result<u64, Error> {
+			if self.env().caller() != self.manager {
+				return Err(Error::NotManager)
+			}
+			if self.unsettled.len() >= MAX_UNSETTLED_BETS {
+				return Err(Error::TooManyBetsInPlay)
+			}
+
+			let bet_id = self.env().extension().place_bet(self.asset, amount, bet.clone())?;
+			self.unsettled.push(bet_id);
+
+			self.env().emit_event(BetPlaced { bet_id, amount, bet });
+			Ok(bet_id)
+		}
+
+		/// Sets the bet that the vault places, and places again on every settlement, until it
+		/// is unset or cannot be placed. Only the manager can.
+		#[ink(message)]
+		pub fn set_auto_bet(&mut self, auto_bet: Option<(Balance, Bet)>) -> Result<(), Error> {
+			if self.env().caller() != self.manager {
+				return Err(Error::NotManager)
+			}
+
+			self.auto_bet = auto_bet;
+			match self.auto_bet.clone() {
+				Some((amount, bet)) => self.place_auto_bet(amount, bet).map(|_| ()),
+				None => Ok(()),
+			}
+		}
+
+		/// Called back by the runtime on behalf of the vault when one of its bets is settled.
+		/// Claims the winnings of the bet and places the automatic bet again, compounding them.
+		#[ink(message, selector = 0x5E771ED0)]
+		pub fn on_bet_settled(&mut self, settlement: BetSettlement) -> Result<(), Error> {
+			if self.env().caller() != self.env().account_id() {
+				return Err(Error::NotCallback)
+			}
+
+			self.unsettled.retain(|bet_id| *bet_id != settlement.bet_id);
+			if settlement.won {
+				self.claim_credited()?;
+			}
+			self.env().emit_event(BetSettled {
+				bet_id: settlement.bet_id,
+				won: settlement.won,
+				payout: settlement.payout,
+			});
+			match self.auto_bet.clone() {
+				Some((amount, bet)) => self.place_auto_bet(amount, bet).map(|_| ()),
+				None => Ok(()),
+			}
+		}
+
+		/// Claims the winnings credited to the vault into it, returning the amount claimed.
+		/// Anybody can.
+		#[ink(message)]
+		pub fn claim_winnings(&mut self) -> Result<Balance, Error> {
+			self.claim_credited()
+		}
+
+		/// Bets of the vault waiting to be played, with the round that takes them.
+		#[ink(message)]
+		pub fn ongoing_bets(&self) -> Vec<(u32, BetData)> {
+			self.env().extension().ongoing_bets()
+		}
+
+		/// Summary of a bet of the vault that was already played.
+		#[ink(message)]
+		pub fn settled_bet(&self, bet_id: u64) -> Option<SettledBet> {
+			self.env().extension().settled_bet(bet_id)
+		}
+
+		/// Spin that played a round.
+		#[ink(message)]
+		pub fn spin(&self, round: u32) -> Option<SpinRecord> {
+			self.env().extension().spin(round)
+		}
+
+		/// Places the automatic bet, asking to be called back with its settlement.
+		fn place_auto_bet(&mut self, amount: Balance, bet: Bet) -> Result<u64, Error> {
+			if self.unsettled.len() >= MAX_UNSETTLED_BETS {
+				return Err(Error::TooManyBetsInPlay)
+			}
+
+			let bet_id = self.env().extension().place_bet_with_callback(
+				self.asset,
+				amount,
+				bet.clone(),
+				ON_BET_SETTLED,
+			)?;
+			self.unsettled.push(bet_id);
+
+			self.env().emit_event(BetPlaced { bet_id, amount, bet });
+			Ok(bet_id)
+		}
+
+		/// Forgets the bets of the vault that were settled, failing if any is still in play, and
+		/// claims the winnings credited to the vault, so that it holds everything it owns.
+		fn settle_vault(&mut self) -> Result<(), Error> {
+			self.unsettled
+				.retain(|bet_id| Self::env().extension().settled_bet(*bet_id).is_none());
+			if !self.unsettled.is_empty() {
+				return Err(Error::BetsInPlay)
+			}
+			self.claim_credited().map(|_| ())
+		}
+
+		/// Claims the winnings credited to the vault, if any.
+		fn claim_credited(&mut self) -> Result<Balance, Error> {
+			match self.env().extension().claim_winnings(self.asset) {
+				Ok(amount) => Ok(amount),
+				Err(RouletteError::NothingToClaim) => Ok(0),
+				Err(error) => Err(error.into()),
+			}
+		}
+
+		/// Shares of `member`.
+		#[ink(message)]
+		pub fn shares_of(&self, member: AccountId) -> Balance {
+			self.shares.get(member).unwrap_or_default()
+		}
+
+		/// Shares of all the members.
+		#[ink(message)]
+		pub fn total_shares(&self) -> Balance {
+			self.total_shares
+		}
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+		use crate::RouletteColor;
+		use ink_env::{test, DefaultEnvironment};
+		use ink_lang as ink;
+		use scale::{Decode, Encode};
+		use std::{cell::Cell, rc::Rc};
+
+		/// Roulette of the runtime that takes bets up to a house capacity.
+		struct MockRoulette {
+			capacity: Balance,
+		}
+
+		impl test::ChainExtension for MockRoulette {
+			fn func_id(&self) -> u32 {
+				1
+			}
+
+			fn call(&mut self, mut input: &[u8], output: &mut Vec<u8>) -> u32 {
+				let (_asset, amount, bet) = <(u32, Balance, Bet)>::decode(&mut input).unwrap();
+				match bet {
+					Bet::Full(number) if number > 36 => 3,
+					Bet::Full(_) if amount * 36 > self.capacity => 2,
+					_ => {
+						7_u64.encode_to(output);
+						0
+					},
+				}
+			}
+		}
+
+		fn set_caller(caller: AccountId) {
+			test::set_caller::<DefaultEnvironment>(caller);
+		}
+
+		fn deposit(syndicate: &mut Syndicate, amount: Balance) -> Result<Balance, Error> {
+			let contract = test::callee::<DefaultEnvironment>();
+			let balance = test::get_account_balance::<DefaultEnvironment>(contract).unwrap();
+			test::set_account_balance::<DefaultEnvironment>(contract, balance + amount);
+			test::set_value_transferred::<DefaultEnvironment>(amount);
+			syndicate.deposit()
+		}
+
+		#[ink::test]
+		fn shares_follow_the_vault() {
+			test::register_chain_extension(MockClaim::default());
+			let accounts = test::default_accounts::<DefaultEnvironment>();
+			let contract = test::callee::<DefaultEnvironment>();
+			test::set_account_balance::<DefaultEnvironment>(contract, 0);
+			let mut syndicate = Syndicate::new(0);
+
+			set_caller(accounts.bob);
+			assert_eq!(deposit(&mut syndicate, 100), Ok(100));
+
+			// The vault triples after a win, so new shares cost three times as much.
+			test::set_account_balance::<DefaultEnvironment>(contract, 300);
+			set_caller(accounts.charlie);
+			assert_eq!(deposit(&mut syndicate, 150), Ok(50));
+			assert_eq!(syndicate.total_shares(), 150);
+
+			set_caller(accounts.bob);
+			assert_eq!(syndicate.withdraw(101), Err(Error::NotEnoughShares));
+			assert_eq!(syndicate.withdraw(100), Ok(300));
+			assert_eq!(syndicate.shares_of(accounts.bob), 0);
+			assert_eq!(test::get_account_balance::<DefaultEnvironment>(contract), Ok(150));
+		}
+
+		/// Roulette of the runtime whose bets are all settled, or all in play.
+		struct MockSettledBets {
+			settled: bool,
+		}
+
+		impl test::ChainExtension for MockSettledBets {
+			fn func_id(&self) -> u32 {
+				3
+			}
+
+			fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+				let settled = SettledBet { round: 0, won: false, payout: 0, record_hash: [0; 32] };
+				self.settled.then_some(settled).encode_to(output);
+				0
+			}
+		}
+
+		#[ink::test]
+		fn shares_wait_for_the_bets_in_play() {
+			test::register_chain_extension(MockRoulette { capacity: 1_000 });
+			test::register_chain_extension(MockSettledBets { settled: false });
+			test::register_chain_extension(MockClaim::default());
+			let accounts = test::default_accounts::<DefaultEnvironment>();
+			let contract = test::callee::<DefaultEnvironment>();
+			test::set_account_balance::<DefaultEnvironment>(contract, 0);
+			set_caller(accounts.alice);
+			let mut syndicate = Syndicate::new(0);
+
+			set_caller(accounts.bob);
+			assert_eq!(deposit(&mut syndicate, 100), Ok(100));
+			set_caller(accounts.alice);
+			assert_eq!(syndicate.bet(10, Bet::Color(RouletteColor::Red)), Ok(7));
+
+			// The stake is out of the vault until the bet settles, and deposits are sent back.
+			set_caller(accounts.charlie);
+			assert_eq!(deposit(&mut syndicate, 50), Err(Error::BetsInPlay));
+			assert_eq!(test::get_account_balance::<DefaultEnvironment>(contract), Ok(100));
+			set_caller(accounts.bob);
+			assert_eq!(syndicate.withdraw(50), Err(Error::BetsInPlay));
+
+			test::register_chain_extension(MockSettledBets { settled: true });
+			assert_eq!(syndicate.withdraw(50), Ok(50));
+		}
+
+		/// Roulette of the runtime that registers callbacks.
+		struct MockCallbackRoulette;
+
+		impl test::ChainExtension for MockCallbackRoulette {
+			fn func_id(&self) -> u32 {
+				5
+			}
+
+			fn call(&mut self, mut input: &[u8], output: &mut Vec<u8>) -> u32 {
+				let (_, _, _, selector) =
+					<(u32, Balance, Bet, [u8; 4])>::decode(&mut input).unwrap();
+				assert_eq!(selector, ON_BET_SETTLED);
+				9_u64.encode_to(output);
+				0
+			}
+		}
+
+		#[ink::test]
+		fn auto_bets_are_placed_on_every_settlement() {
+			test::register_chain_extension(MockCallbackRoulette);
+			test::register_chain_extension(MockClaim::default());
+			let accounts = test::default_accounts::<DefaultEnvironment>();
+			set_caller(accounts.alice);
+			let mut syndicate = Syndicate::new(0);
+
+			let settlement =
+				BetSettlement { bet_id: 9, round: 0, outcome: 1, won: true, payout: 20 };
+			assert_eq!(syndicate.on_bet_settled(settlement.clone()), Err(Error::NotCallback));
+
+			let auto_bet = Some((10, Bet::Color(RouletteColor::Red)));
+			assert_eq!(syndicate.set_auto_bet(auto_bet), Ok(()));
+			assert_eq!(test::recorded_events().count(), 1);
+
+			set_caller(test::callee::<DefaultEnvironment>());
+			assert_eq!(syndicate.on_bet_settled(settlement.clone()), Ok(()));
+			assert_eq!(test::recorded_events().count(), 3);
+
+			// Without an automatic bet, settlements are only reported.
+			set_caller(accounts.alice);
+			assert_eq!(syndicate.set_auto_bet(None), Ok(()));
+			set_caller(test::callee::<DefaultEnvironment>());
+			assert_eq!(syndicate.on_bet_settled(settlement), Ok(()));
+			assert_eq!(test::recorded_events().count(), 4);
+		}
+
+		/// Roulette of the runtime that credited `pending` winnings to the vault.
+		#[derive(Default)]
+		struct MockClaim {
+			pending: Rc<Cell<Balance>>,
+		}
+
+		impl test::ChainExtension for MockClaim {
+			fn func_id(&self) -> u32 {
+				6
+			}
+
+			fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+				match self.pending.take() {
+					0 => 5,
+					pending => {
+						pending.encode_to(output);
+						0
+					},
+				}
+			}
+		}
+
+		#[ink::test]
+		fn credited_winnings_are_claimed_into_the_vault() {
+			let pending = Rc::new(Cell::new(0));
+			test::register_chain_extension(MockClaim { pending: pending.clone() });
+			let accounts = test::default_accounts::<DefaultEnvironment>();
+			let contract = test::callee::<DefaultEnvironment>();
+			test::set_account_balance::<DefaultEnvironment>(contract, 0);
+			set_caller(accounts.alice);
+			let mut syndicate = Syndicate::new(0);
+
+			pending.set(30);
+			assert_eq!(syndicate.claim_winnings(), Ok(30));
+			assert_eq!(syndicate.claim_winnings(), Ok(0));
+
+			// Winnings are claimed before shares are priced.
+			pending.set(20);
+			set_caller(accounts.bob);
+			assert_eq!(deposit(&mut syndicate, 100), Ok(100));
+			assert_eq!(pending.get(), 0);
+
+			// And when a bet of the vault wins.
+			pending.set(20);
+			set_caller(contract);
+			let settlement =
+				BetSettlement { bet_id: 9, round: 0, outcome: 1, won: true, payout: 20 };
+			assert_eq!(syndicate.on_bet_settled(settlement), Ok(()));
+			assert_eq!(pending.get(), 0);
+		}
+
+		#[ink::test]
+		fn only_the_manager_bets() {
+			test::register_chain_extension(MockRoulette { capacity: 1_000 });
+			let accounts = test::default_accounts::<DefaultEnvironment>();
+			set_caller(accounts.alice);
+			let mut syndicate = Syndicate::new(0);
+
+			assert_eq!(syndicate.bet(10, Bet::Color(RouletteColor::Red)), Ok(7));
+			assert_eq!(
+				syndicate.bet(10, Bet::Full(37)),
+				Err(Error::Roulette(RouletteError::InvalidBet))
+			);
+			assert_eq!(
+				syndicate.bet(100, Bet::Full(5)),
+				Err(Error::Roulette(RouletteError::NotEnoughHouseBalance))
+			);
+
+			set_caller(accounts.bob);
+			assert_eq!(syndicate.bet(10, Bet::Color(RouletteColor::Red)), Err(Error::NotManager));
+		}
+	}
+}
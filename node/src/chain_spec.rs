@@ -1,16 +1,33 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AssetId, AssetsConfig, AuraConfig, Balance, BalancesConfig, CouncilConfig,
+	GenesisConfig, GrandpaConfig, LotteryConfig, MaxBeaconUrlLength, NativeAssetId,
+	RoulettePalletId, Signature, SudoConfig, SystemConfig, DOLLARS, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{sr25519, Pair, Public};
+use sp_core::{ed25519, sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
-use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_runtime::{
+	traits::{AccountIdConversion, Get, IdentifyAccount, Verify},
+	BoundedVec,
+};
+use traits::{BeaconInfo, RandomnessSource};
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
+/// Asset created by the `roulette-dev` chain to bet with besides the native currency.
+const DEMO_ASSET_ID: AssetId = 1;
+/// Balance of the house of the `roulette-dev` chain, in the native currency and the demo asset.
+const HOUSE_BANKROLL: Balance = 1_000_000 * DOLLARS;
+/// Demo asset given to every player of the `roulette-dev` chain.
+const PLAYER_DEMO_BALANCE: Balance = 10_000 * DOLLARS;
+
+/// URL of the randomness beacon of the local testnet.
+const LOCAL_BEACON_URL: &[u8] = b"http://localhost:8090";
+/// Seconds between two rounds of the randomness beacon of the local testnet.
+const LOCAL_BEACON_PERIOD: u64 = 3;
+
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
 
@@ -52,6 +69,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				vec![authority_keys_from_seed("Alice")],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Council that administers the lottery
+				vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
 				// Pre-funded accounts
 				vec![
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -59,6 +78,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// Games settle on block hashes, without a beacon to run
+				RandomnessSource::BlockHash,
 				true,
 			)
 		},
@@ -92,6 +113,12 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Council that administers the lottery
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+				],
 				// Pre-funded accounts
 				vec![
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -107,6 +134,8 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Games settle on the rounds of a local beacon
+				local_beacon(),
 				true,
 			)
 		},
@@ -124,12 +153,94 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
+/// Development chain ready to play the roulette: the house is funded, tables have demo limits and
+/// the well-known accounts are endowed with the native currency and a demo asset.
+pub fn roulette_development_config() -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Roulette Development",
+		// ID
+		"roulette_dev",
+		ChainType::Development,
+		move || {
+			let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+			let players: Vec<AccountId> = ["Alice", "Bob", "Charlie", "Dave", "Eve", "Ferdie"]
+				.iter()
+				.map(|seed| get_account_id_from_seed::<sr25519::Public>(seed))
+				.collect();
+			let house: AccountId = RoulettePalletId::get().into_account_truncating();
+
+			let mut genesis = testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice")],
+				// Sudo account
+				alice.clone(),
+				// Council that administers the lottery
+				vec![alice.clone()],
+				// Pre-funded accounts
+				players.clone(),
+				// Games settle on block hashes, without a beacon to run
+				RandomnessSource::BlockHash,
+				true,
+			);
+
+			// The house covers the payouts of the bets, in every asset it takes bets in.
+			genesis.balances.balances.push((house.clone(), HOUSE_BANKROLL));
+			genesis.assets = AssetsConfig {
+				assets: vec![(DEMO_ASSET_ID, alice, true, 1)],
+				metadata: vec![(DEMO_ASSET_ID, b"Demo Dollar".to_vec(), b"DUSD".to_vec(), 14)],
+				accounts: players
+					.into_iter()
+					.map(|player| (DEMO_ASSET_ID, player, PLAYER_DEMO_BALANCE))
+					.chain([(DEMO_ASSET_ID, house, HOUSE_BANKROLL)])
+					.collect(),
+			};
+			genesis.lottery = LotteryConfig {
+				paused: false,
+				bet_limits: vec![
+					(NativeAssetId::get(), DOLLARS, 100 * DOLLARS),
+					(DEMO_ASSET_ID, DOLLARS, 100 * DOLLARS),
+				],
+				randomness_source: Some(RandomnessSource::BlockHash),
+			};
+			genesis
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		None,
+		// Properties
+		None,
+		// Extensions
+		None,
+	))
+}
+
+/// Randomness beacon of the local testnet, signing its rounds with the ed25519 key of `//Alice`
+/// since the unix epoch.
+fn local_beacon() -> RandomnessSource<BoundedVec<u8, MaxBeaconUrlLength>> {
+	RandomnessSource::Beacon(BeaconInfo {
+		public_key: get_from_seed::<ed25519::Public>("Alice").0,
+		genesis_time: 0,
+		period: LOCAL_BEACON_PERIOD,
+		url: BoundedVec::truncate_from(LOCAL_BEACON_URL.to_vec()),
+	})
+}
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
+	council_members: Vec<AccountId>,
 	endowed_accounts: Vec<AccountId>,
+	randomness_source: RandomnessSource<BoundedVec<u8, MaxBeaconUrlLength>>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -141,6 +252,7 @@ fn testnet_genesis(
 			// Configure endowed accounts with initial balance of 1 << 60.
 			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
 		},
+		assets: Default::default(),
 		aura: AuraConfig {
 			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
 		},
@@ -151,6 +263,8 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: Some(root_key),
 		},
+		council: CouncilConfig { members: council_members, phantom: Default::default() },
+		lottery: LotteryConfig { randomness_source: Some(randomness_source), ..Default::default() },
 		transaction_payment: Default::default(),
 		vesting: Default::default(),
 	}
@@ -1,3 +1,4 @@
+use crate::{export::ExportCmd, simulate::SimulateCmd};
 use sc_cli::RunCmd;
 
 #[derive(Debug, clap::Parser)]
@@ -7,6 +8,23 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks on demand instead of in Aura slots, for development and integration tests.
+	/// `instant` seals a block as soon as a transaction enters the pool, `manual` seals one on
+	/// every `engine_createBlock` RPC call. Grandpa does not run in either mode. Block timestamps
+	/// follow block numbers, so with a chain spec whose games settle on block hashes, such as
+	/// `dev` or `roulette-dev`, the same transactions always give the same outcomes.
+	#[clap(long, arg_enum)]
+	pub sealing: Option<Sealing>,
+}
+
+/// How blocks are sealed on demand.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ArgEnum)]
+pub enum Sealing {
+	/// Seal a block with every transaction that enters the pool.
+	Instant,
+	/// Seal blocks when asked to through the `engine_createBlock` RPC.
+	Manual,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -50,4 +68,17 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Lottery tooling.
+	#[clap(subcommand)]
+	Lottery(LotteryCmd),
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum LotteryCmd {
+	/// Simulate a mix of roulette bets to measure the house edge and size the bankroll.
+	Simulate(SimulateCmd),
+
+	/// Export settled bets and round results from the local database as CSV or JSON Lines.
+	Export(ExportCmd),
 }
@@ -0,0 +1,423 @@
+//! Export of the lottery history kept in the local database. Blocks are read in order: settled
+//! bets come from the `BetSettled` and `BetRejected` events, and round results come from the
+//! `RoulettePlayed` events. The details of a bet come from its record in the offchain index,
+//! which is only kept by nodes started with `--enable-offchain-indexing`, or else from the
+//! `BetPlaced` event of the bet when it is within the exported blocks.
+//!
+//! The events and storage of a block are only kept while its state is, so exporting old blocks
+//! needs a node that ran with `--state-pruning archive`. Blocks whose events this runtime cannot
+//! decode, such as blocks of older runtimes, are skipped and reported on the standard error.
+
+use crate::service::FullClient;
+use codec::Decode;
+use node_template_runtime::{
+	opaque::Block,
+	pallet_lottery::{self, bet_record_key},
+	AccountId, AssetId, Balance, BlockNumber, Hash, Runtime, RuntimeEvent,
+};
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::StorageProvider;
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::Ss58Codec,
+	hashing::twox_128,
+	offchain::{OffchainStorage, STORAGE_PREFIX},
+	storage::StorageKey,
+};
+use sp_runtime::generic::BlockId;
+use std::{
+	collections::HashMap,
+	fs::File,
+	io::{self, BufWriter, Write},
+	path::PathBuf,
+	sync::Arc,
+};
+use traits::{Bet, BetRecord};
+
+type LotteryEvent = pallet_lottery::Event<Runtime>;
+
+/// Details of a bet, as placed.
+struct PlacedBet {
+	owner: AccountId,
+	asset: AssetId,
+	bet: Bet,
+	amount: Balance,
+	voucher: Option<u64>,
+}
+
+/// Format of the exported history.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ArgEnum)]
+pub enum ExportFormat {
+	/// Comma separated values, with a header.
+	Csv,
+	/// One JSON object per line.
+	Jsonl,
+}
+
+/// The `export` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ExportCmd {
+	/// First block to read.
+	#[clap(long, default_value = "1")]
+	pub from: BlockNumber,
+
+	/// Last block to read. Defaults to the best block.
+	#[clap(long)]
+	pub to: Option<BlockNumber>,
+
+	/// Only export the bets of this account, given in SS58. Round results are still exported.
+	#[clap(long)]
+	pub account: Option<AccountId>,
+
+	/// Only export bets and results of this round onwards.
+	#[clap(long)]
+	pub from_round: Option<u32>,
+
+	/// Only export bets and results up to this round.
+	#[clap(long)]
+	pub to_round: Option<u32>,
+
+	/// Format of the output.
+	#[clap(long, arg_enum, default_value = "csv")]
+	pub format: ExportFormat,
+
+	/// File to write to. Defaults to the standard output.
+	#[clap(long)]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// A row of the exported history.
+#[derive(Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum Record {
+	/// A settled bet. The payout of pending bets is credited to their owner instead of paid, and
+	/// rejected bets have no payout.
+	Bet {
+		block: BlockNumber,
+		round: u32,
+		bet_id: u64,
+		account: String,
+		asset: AssetId,
+		bet: String,
+		amount: Balance,
+		voucher: Option<u64>,
+		won: bool,
+		payout: Option<Balance>,
+		pending: bool,
+	},
+	/// The result of a round in an asset.
+	Round {
+		block: BlockNumber,
+		round: u32,
+		asset: AssetId,
+		seed: String,
+		winner_number: u32,
+		players: u32,
+		income: Balance,
+		payout: Balance,
+	},
+}
+
+const CSV_HEADER: &str = "record,block,round,bet_id,account,asset,bet,amount,voucher,won,payout,\
+	pending,seed,winner_number,players,income";
+
+impl Record {
+	fn round(&self) -> u32 {
+		match self {
+			Record::Bet { round, .. } | Record::Round { round, .. } => *round,
+		}
+	}
+
+	fn csv(&self) -> String {
+		let optional = |value: Option<String>| value.unwrap_or_default();
+		let fields = match self {
+			Record::Bet {
+				block,
+				round,
+				bet_id,
+				account,
+				asset,
+				bet,
+				amount,
+				voucher,
+				won,
+				payout,
+				pending,
+			} => [
+				"bet".to_string(),
+				block.to_string(),
+				round.to_string(),
+				bet_id.to_string(),
+				account.clone(),
+				asset.to_string(),
+				bet.clone(),
+				amount.to_string(),
+				optional(voucher.map(|voucher| voucher.to_string())),
+				won.to_string(),
+				optional(payout.map(|payout| payout.to_string())),
+				pending.to_string(),
+				String::new(),
+				String::new(),
+				String::new(),
+				String::new(),
+			],
+			Record::Round { block, round, asset, seed, winner_number, players, income, payout } =>
+				[
+					"round".to_string(),
+					block.to_string(),
+					round.to_string(),
+					String::new(),
+					String::new(),
+					asset.to_string(),
+					String::new(),
+					String::new(),
+					String::new(),
+					String::new(),
+					payout.to_string(),
+					String::new(),
+					seed.clone(),
+					winner_number.to_string(),
+					players.to_string(),
+					income.to_string(),
+				],
+		};
+		fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",")
+	}
+}
+
+/// Quotes a CSV field if it needs to.
+fn csv_field(field: &str) -> String {
+	if field.contains(|c| matches!(c, ',' | '"' | '\n')) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+/// Key of a storage value, or prefix of a storage map.
+fn storage_prefix(pallet: &str, item: &str) -> Vec<u8> {
+	[twox_128(pallet.as_bytes()), twox_128(item.as_bytes())].concat()
+}
+
+impl ExportCmd {
+	/// Walks the blocks and writes the lottery history they hold, reading the details of bets from
+	/// the offchain index of the node when there is one.
+	pub fn run<S: OffchainStorage>(
+		&self,
+		client: Arc<FullClient>,
+		offchain_storage: Option<S>,
+	) -> sc_cli::Result<()> {
+		let to = self.to.unwrap_or_else(|| client.info().best_number);
+		if self.from > to {
+			return Err(format!("There are no blocks from {} to {}.", self.from, to).into())
+		}
+
+		let mut out: Box<dyn Write> = match &self.output {
+			Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+			None => Box::new(BufWriter::new(io::stdout())),
+		};
+		if self.format == ExportFormat::Csv {
+			writeln!(out, "{}", CSV_HEADER)?;
+		}
+
+		let events_key = StorageKey(storage_prefix("System", "Events"));
+		let mut placed = HashMap::new();
+		let mut undecodable = Vec::new();
+		for number in self.from..=to {
+			let hash = match client.hash(number)? {
+				Some(hash) => hash,
+				None => break,
+			};
+			let at = BlockId::<Block>::Hash(hash);
+			let events = match client.storage(&at, &events_key)? {
+				Some(events) => match Vec::<frame_system::EventRecord<RuntimeEvent, Hash>>::decode(
+					&mut &events.0[..],
+				) {
+					Ok(events) => events,
+					Err(e) => {
+						eprintln!("Skipping block {}: cannot decode its events: {}", number, e);
+						undecodable.push(number);
+						continue
+					},
+				},
+				// The state of the block was pruned.
+				None => continue,
+			};
+
+			for record in self.records(offchain_storage.as_ref(), &mut placed, number, events)? {
+				let round = record.round();
+				if self.from_round.map_or(false, |from| round < from) ||
+					self.to_round.map_or(false, |to| round > to)
+				{
+					continue
+				}
+				match self.format {
+					ExportFormat::Csv => writeln!(out, "{}", record.csv())?,
+					ExportFormat::Jsonl => writeln!(
+						out,
+						"{}",
+						serde_json::to_string(&record).map_err(|e| e.to_string())?
+					)?,
+				}
+			}
+		}
+
+		out.flush()?;
+		if !undecodable.is_empty() {
+			eprintln!(
+				"Skipped {} blocks whose events could not be decoded, and the bets settled in them: \
+				{:?}",
+				undecodable.len(),
+				undecodable
+			);
+		}
+		Ok(())
+	}
+
+	/// History recorded by the events of a block. Bets are settled before the results of their
+	/// round are announced, so settled bets wait for the next round result to know their round.
+	/// Bets placed in the block by the exported accounts are added to `placed` until they are
+	/// settled.
+	fn records<S: OffchainStorage>(
+		&self,
+		offchain_storage: Option<&S>,
+		placed: &mut HashMap<u64, PlacedBet>,
+		block: BlockNumber,
+		events: Vec<frame_system::EventRecord<RuntimeEvent, Hash>>,
+	) -> sc_cli::Result<Vec<Record>> {
+		let mut records = Vec::new();
+		let mut settled: Vec<(u64, bool, Option<Balance>, bool)> = Vec::new();
+
+		for event in events {
+			let event = match event.event {
+				RuntimeEvent::Lottery(event) => event,
+				_ => continue,
+			};
+			match event {
+				LotteryEvent::BetPlaced { bet_id, who, bet, asset, amount, .. } =>
+					if self.exports(&who) {
+						placed.insert(
+							bet_id,
+							PlacedBet { owner: who, asset, bet, amount, voucher: None },
+						);
+					},
+				LotteryEvent::VoucherRedeemed { voucher_id, bet_id } =>
+					if let Some(placed) = placed.get_mut(&bet_id) {
+						placed.voucher = Some(voucher_id);
+					},
+				LotteryEvent::BetSettled { bet_id, who, won, payout, pending } =>
+					if self.exports(&who) {
+						settled.push((bet_id, won, Some(payout), pending));
+					},
+				LotteryEvent::BetRejected { bet_id, who, .. } =>
+					if self.exports(&who) {
+						settled.push((bet_id, false, None, false));
+					},
+				LotteryEvent::RoulettePlayed {
+					round,
+					asset,
+					seed,
+					winner_number,
+					players,
+					income,
+					payout,
+				} => {
+					for (bet_id, won, payout, pending) in settled.drain(..) {
+						let bet_data = Self::placed_bet(offchain_storage, placed, bet_id)?;
+						records.push(Record::Bet {
+							block,
+							round,
+							bet_id,
+							account: bet_data.owner.to_ss58check(),
+							asset: bet_data.asset,
+							bet: format!("{:?}", bet_data.bet),
+							amount: bet_data.amount,
+							voucher: bet_data.voucher,
+							won,
+							payout,
+							pending,
+						});
+					}
+					records.push(Record::Round {
+						block,
+						round,
+						asset,
+						seed: format!("{:?}", seed),
+						winner_number,
+						players,
+						income,
+						payout,
+					});
+				},
+				_ => {},
+			}
+		}
+
+		Ok(records)
+	}
+
+	/// Whether the bets of `who` are exported.
+	fn exports(&self, who: &AccountId) -> bool {
+		self.account.as_ref().map_or(true, |account| account == who)
+	}
+
+	/// Details of a settled bet, from its offchain record or else from its `BetPlaced` event.
+	fn placed_bet<S: OffchainStorage>(
+		offchain_storage: Option<&S>,
+		placed: &mut HashMap<u64, PlacedBet>,
+		bet_id: u64,
+	) -> sc_cli::Result<PlacedBet> {
+		let placed_bet = placed.remove(&bet_id);
+		let encoded = offchain_storage
+			.and_then(|storage| storage.get(STORAGE_PREFIX, &bet_record_key(bet_id)));
+		if let Some(encoded) = encoded {
+			let record =
+				BetRecord::<AccountId, AssetId, BlockNumber, Balance>::decode(&mut &encoded[..])
+					.map_err(|e| format!("Cannot decode the record of bet {}: {}", bet_id, e))?;
+			return Ok(PlacedBet {
+				owner: record.bet.owner,
+				asset: record.bet.asset,
+				bet: record.bet.bet,
+				amount: record.bet.amount,
+				voucher: record.bet.voucher,
+			})
+		}
+
+		placed_bet.ok_or_else(|| {
+			format!(
+				"Bet {} was placed before the exported blocks and is not in the offchain index. \
+				Export from an earlier block, or from a node started with \
+				`--enable-offchain-indexing`.",
+				bet_id
+			)
+			.into()
+		})
+	}
+}
+
+impl CliConfiguration for ExportCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -1,3 +1,6 @@
 pub mod chain_spec;
+pub mod cli;
+pub mod export;
 pub mod rpc;
 pub mod service;
+pub mod simulate;
@@ -7,7 +7,9 @@ mod service;
 mod benchmarking;
 mod cli;
 mod command;
+mod export;
 mod rpc;
+mod simulate;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
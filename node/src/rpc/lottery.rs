@@ -0,0 +1,97 @@
+//! History of settled bets, read from the records that the lottery pallet writes to the offchain
+//! index. Only nodes started with `--enable-offchain-indexing` keep those records.
+
+use codec::Decode;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::{
+	pallet_lottery::{account_bet_key, bet_record_key},
+	AccountId, AssetId, Balance, BlockNumber,
+};
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+use traits::BetRecord;
+
+/// Most bets returned by a single `lottery_betsOf` call.
+const MAX_BETS: u32 = 100;
+
+/// Record of a settled bet.
+pub type Record = BetRecord<AccountId, AssetId, BlockNumber, Balance>;
+
+/// History of settled bets.
+#[rpc(server)]
+pub trait LotteryApi {
+	/// Record of a settled bet.
+	#[method(name = "lottery_settledBet")]
+	fn settled_bet(&self, bet_id: u64) -> RpcResult<Option<Record>>;
+
+	/// Records of the settled bets of an account, in the order in which they were settled,
+	/// starting from its `from`-th bet. At most 100 bets are returned at once.
+	#[method(name = "lottery_betsOf")]
+	fn bets_of(
+		&self,
+		who: AccountId,
+		from: Option<u32>,
+		limit: Option<u32>,
+	) -> RpcResult<Vec<Record>>;
+}
+
+/// Implements the [`LotteryApiServer`] RPC trait over the offchain storage of the node.
+pub struct Lottery<S> {
+	storage: S,
+}
+
+impl<S> Lottery<S> {
+	/// Create new `Lottery` with the given offchain storage.
+	pub fn new(storage: S) -> Self {
+		Self { storage }
+	}
+}
+
+impl<S: OffchainStorage + 'static> Lottery<S> {
+	fn get<V: Decode>(&self, key: &[u8]) -> RpcResult<Option<V>> {
+		self.storage
+			.get(STORAGE_PREFIX, key)
+			.map(|encoded| V::decode(&mut &encoded[..]))
+			.transpose()
+			.map_err(|e| {
+				CallError::Custom(ErrorObject::owned(
+					1,
+					"Cannot decode the offchain record.",
+					Some(e.to_string()),
+				))
+				.into()
+			})
+	}
+}
+
+impl<S: OffchainStorage + 'static> LotteryApiServer for Lottery<S> {
+	fn settled_bet(&self, bet_id: u64) -> RpcResult<Option<Record>> {
+		self.get(&bet_record_key(bet_id))
+	}
+
+	fn bets_of(
+		&self,
+		who: AccountId,
+		from: Option<u32>,
+		limit: Option<u32>,
+	) -> RpcResult<Vec<Record>> {
+		let from = from.unwrap_or(0);
+		let limit = limit.unwrap_or(MAX_BETS).min(MAX_BETS);
+
+		let mut records = Vec::new();
+		for index in from..from.saturating_add(limit) {
+			// Bets of an account are indexed in a row, so the first missing one ends them.
+			let bet_id: u64 = match self.get(&account_bet_key(&who, index))? {
+				Some(bet_id) => bet_id,
+				None => break,
+			};
+			if let Some(record) = self.get(&bet_record_key(bet_id))? {
+				records.push(record);
+			}
+		}
+		Ok(records)
+	}
+}
@@ -1,14 +1,24 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use crate::cli::Sealing;
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::BlockBackend;
-use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+use sc_client_api::{Backend, BlockBackend};
+use sc_consensus_aura::{ImportQueueParams, SlotDuration, SlotProportion, StartAuraParams};
+use sc_consensus_manual_seal::{
+	consensus::aura::AuraConsensusDataProvider, InstantSealParams, ManualSealParams,
+};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
+use sp_blockchain::HeaderBackend;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use sp_timestamp::Timestamp;
 use std::{sync::Arc, time::Duration};
 
 // Our native executor instance.
@@ -38,6 +48,7 @@ type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
 pub fn new_partial(
 	config: &Configuration,
+	sealing: Option<Sealing>,
 ) -> Result<
 	sc_service::PartialComponents<
 		FullClient,
@@ -112,7 +123,14 @@ pub fn new_partial(
 
 	let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
 
-	let import_queue =
+	// Blocks sealed on demand are not signed, so they cannot go through the Aura import queue.
+	let import_queue = if sealing.is_some() {
+		sc_consensus_manual_seal::import_queue(
+			Box::new(grandpa_block_import.clone()),
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+		)
+	} else {
 		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _>(ImportQueueParams {
 			block_import: grandpa_block_import.clone(),
 			justification_import: Some(Box::new(grandpa_block_import.clone())),
@@ -132,7 +150,8 @@ pub fn new_partial(
 			registry: config.prometheus_registry(),
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+		})?
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -153,8 +172,28 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 	Err("Remote Keystore not supported.")
 }
 
+/// Timestamp of a block sealed on demand: the start of the slot numbered after the block. Every
+/// block is in a new Aura slot however fast blocks are sealed, and sealing the same transactions
+/// always gives the same timestamps, block hashes and so outcomes of the games.
+fn sealing_timestamp(
+	client: &FullClient,
+	parent: <Block as BlockT>::Hash,
+	slot_duration: SlotDuration,
+) -> Timestamp {
+	let parent_number = client
+		.header(BlockId::Hash(parent))
+		.ok()
+		.flatten()
+		.map_or(0, |header| u64::from(*header.number()));
+
+	Timestamp::new(parent_number.saturating_add(1).saturating_mul(slot_duration.as_millis()))
+}
+
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(
+	mut config: Configuration,
+	sealing: Option<Sealing>,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -164,7 +203,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		select_chain,
 		transaction_pool,
 		other: (block_import, grandpa_link, mut telemetry),
-	} = new_partial(&config)?;
+	} = new_partial(&config, sealing)?;
 
 	if let Some(url) = &config.keystore_remote {
 		match remote_keystore(url) {
@@ -218,13 +257,28 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	// Blocks sealed manually are requested through the `engine_createBlock` RPC.
+	let (command_sink, commands_stream) = futures::channel::mpsc::channel(1024);
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let command_sink = (sealing == Some(Sealing::Manual)).then(|| command_sink);
+		// The history of the lottery is only kept with `--enable-offchain-indexing`.
+		let offchain_storage = config
+			.offchain_worker
+			.indexing_enabled
+			.then(|| backend.offchain_storage())
+			.flatten();
 
 		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				command_sink: command_sink.clone(),
+				offchain_storage: offchain_storage.clone(),
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
@@ -243,6 +297,64 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let Some(sealing) = sealing {
+		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+			task_manager.spawn_handle(),
+			client.clone(),
+			transaction_pool.clone(),
+			prometheus_registry.as_ref(),
+			telemetry.as_ref().map(|x| x.handle()),
+		);
+
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+		let create_inherent_data_providers = {
+			let client = client.clone();
+			move |parent, ()| {
+				let timestamp = sp_timestamp::InherentDataProvider::new(sealing_timestamp(
+					&client,
+					parent,
+					slot_duration,
+				));
+				async move { Ok(timestamp) }
+			}
+		};
+		// Blocks still carry the Aura slot that the runtime expects.
+		let consensus_data_provider = Box::new(AuraConsensusDataProvider::new(client.clone()));
+
+		match sealing {
+			Sealing::Instant => task_manager.spawn_essential_handle().spawn_blocking(
+				"instant-seal",
+				Some("block-authoring"),
+				sc_consensus_manual_seal::run_instant_seal(InstantSealParams {
+					block_import,
+					env: proposer_factory,
+					client,
+					pool: transaction_pool,
+					select_chain,
+					consensus_data_provider: Some(consensus_data_provider),
+					create_inherent_data_providers,
+				}),
+			),
+			Sealing::Manual => task_manager.spawn_essential_handle().spawn_blocking(
+				"manual-seal",
+				Some("block-authoring"),
+				sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+					block_import,
+					env: proposer_factory,
+					client,
+					pool: transaction_pool,
+					commands_stream,
+					select_chain,
+					consensus_data_provider: Some(consensus_data_provider),
+					create_inherent_data_providers,
+				}),
+			),
+		}
+
+		network_starter.start_network();
+		return Ok(task_manager)
+	}
+
 	if role.is_authority() {
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
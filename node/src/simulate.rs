@@ -0,0 +1,258 @@
+//! Monte Carlo simulation of the house edge of a mix of roulette bets. Spins are derived and bets
+//! are settled with the same functions as the lottery pallet, so the results reflect what the
+//! chain would pay.
+
+use codec::Encode;
+use sp_core::hashing::blake2_256;
+use std::str::FromStr;
+use traits::{Bet, BetKind, DozenOrColumn, Game, Half, OddOrEven, Roulette, RouletteColor};
+
+/// A bet of the simulated mix and its stake, written as `<bet>[:<stake>]`.
+#[derive(Debug, Clone)]
+pub struct MixedBet {
+	pub bet: Bet,
+	pub stake: u128,
+}
+
+impl FromStr for MixedBet {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (bet, stake) = match s.split_once(':') {
+			Some((bet, stake)) => (
+				bet,
+				stake.parse::<u128>().map_err(|e| format!("Invalid stake `{}`: {}", stake, e))?,
+			),
+			None => (s, 1),
+		};
+		if stake == 0 {
+			return Err(format!("Bet `{}` has no stake", s))
+		}
+
+		let third = |n: &str| match n {
+			"1" => Ok(DozenOrColumn::First),
+			"2" => Ok(DozenOrColumn::Second),
+			"3" => Ok(DozenOrColumn::Third),
+			_ => Err(format!("Dozens and columns go from 1 to 3, not `{}`", n)),
+		};
+		let bet = match bet {
+			"red" => Bet::Color(RouletteColor::Red),
+			"black" => Bet::Color(RouletteColor::Black),
+			"odd" => Bet::OddOrEven(OddOrEven::Odd),
+			"even" => Bet::OddOrEven(OddOrEven::Even),
+			"low" => Bet::Half(Half::First),
+			"high" => Bet::Half(Half::Second),
+			other => match other.split_once('-') {
+				Some(("full", n)) =>
+					Bet::Full(n.parse().map_err(|e| format!("Invalid number `{}`: {}", n, e))?),
+				Some(("dozen", n)) => Bet::Dozen(third(n)?),
+				Some(("column", n)) => Bet::Column(third(n)?),
+				_ => return Err(format!("Unknown bet `{}`", other)),
+			},
+		};
+		if !Roulette::validate(&bet) {
+			return Err(format!("Bet `{}` cannot be played", s))
+		}
+
+		Ok(Self { bet, stake })
+	}
+}
+
+/// The `simulate` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct SimulateCmd {
+	/// Bets placed in every spin, as `<bet>[:<stake>]`. Bets are `red`, `black`, `odd`, `even`,
+	/// `low`, `high`, `dozen-<1..3>`, `column-<1..3>` and `full-<0..36>`. Stakes default to 1.
+	#[clap(long = "bet", required = true)]
+	pub bets: Vec<MixedBet>,
+
+	/// Bankroll of the house at the start of every trial.
+	#[clap(long)]
+	pub bankroll: u128,
+
+	/// Spins played in every trial.
+	#[clap(long, default_value = "10000")]
+	pub spins: u32,
+
+	/// Independent trials, each starting again from the bankroll.
+	#[clap(long, default_value = "100")]
+	pub trials: u32,
+
+	/// Highest risk of ruin, in percent, that the required bankroll is sized for.
+	#[clap(long, default_value = "1")]
+	pub max_ruin: f64,
+
+	/// Seed of the spins, so that simulations can be reproduced.
+	#[clap(long, default_value = "0")]
+	pub seed: u64,
+}
+
+/// What a kind of bet was staked and paid.
+struct KindStats {
+	kind: BetKind,
+	/// Stake of the kind in every spin.
+	stake: u128,
+	/// Return expected from the stake of every spin.
+	expected: f64,
+	staked: u128,
+	returned: u128,
+}
+
+impl SimulateCmd {
+	/// Runs the simulation and prints its report.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		if self.spins == 0 || self.trials == 0 {
+			return Err("At least one trial of one spin has to be simulated.".into())
+		}
+		if !(0.0..100.0).contains(&self.max_ruin) {
+			return Err("The risk of ruin is a percentage below 100.".into())
+		}
+
+		let outcomes = Roulette::outcomes();
+		let stakes = self.bets.iter().map(|mixed| mixed.stake).sum::<u128>();
+		let max_payout = (0..outcomes).map(|outcome| self.payout(outcome)).max().unwrap_or(0);
+
+		let mut kinds: Vec<KindStats> = Vec::new();
+		for mixed in &self.bets {
+			let kind = Roulette::kind(&mixed.bet);
+			let wins = (0..outcomes).filter(|outcome| Roulette::is_winner(&mixed.bet, *outcome));
+			let expected = wins.count() as f64 * amount_won(mixed) as f64 / outcomes as f64;
+			match kinds.iter_mut().find(|stats| stats.kind == kind) {
+				Some(stats) => {
+					stats.stake += mixed.stake;
+					stats.expected += expected;
+				},
+				None => kinds.push(KindStats {
+					kind,
+					stake: mixed.stake,
+					expected,
+					staked: 0,
+					returned: 0,
+				}),
+			}
+		}
+
+		// Result of the house in every spin, accumulated with Welford's algorithm.
+		let (mut count, mut mean, mut m2) = (0_f64, 0_f64, 0_f64);
+		// Smallest bankroll that takes every bet of a trial.
+		let mut needs = Vec::with_capacity(self.trials as usize);
+
+		for trial in 0..self.trials {
+			let seed = blake2_256(&(self.seed, trial).encode());
+			let mut cumulative = 0_i128;
+			let mut need = 0_i128;
+
+			for spin in 0..self.spins {
+				// The pallet takes a bet once the stakes are in and the house covers the payout.
+				need = need.max(max_payout as i128 - stakes as i128 - cumulative);
+
+				let outcome =
+					traits::verify_outcome::<Roulette>(&seed, &(b"roulette", spin).encode());
+				let mut paid = 0_u128;
+				for mixed in &self.bets {
+					let won = if Roulette::is_winner(&mixed.bet, outcome) {
+						amount_won(mixed)
+					} else {
+						0
+					};
+					let kind = Roulette::kind(&mixed.bet);
+					if let Some(stats) = kinds.iter_mut().find(|stats| stats.kind == kind) {
+						stats.staked += mixed.stake;
+						stats.returned += won;
+					}
+					paid += won;
+				}
+
+				let result = stakes as i128 - paid as i128;
+				cumulative += result;
+
+				count += 1.0;
+				let delta = result as f64 - mean;
+				mean += delta / count;
+				m2 += delta * (result as f64 - mean);
+			}
+
+			needs.push(need.max(0) as u128);
+		}
+
+		let variance = if count > 1.0 { m2 / (count - 1.0) } else { 0.0 };
+		let ruined = needs.iter().filter(|need| **need > self.bankroll).count();
+		needs.sort_unstable();
+		let covered = ((1.0 - self.max_ruin / 100.0) * self.trials as f64).ceil() as usize;
+		let required = needs[covered.clamp(1, needs.len()) - 1];
+
+		println!(
+			"Simulated {} trials of {} spins, {} spins in total.",
+			self.trials,
+			self.spins,
+			u64::from(self.trials) * u64::from(self.spins)
+		);
+		println!();
+		println!(
+			"{:<12} {:>20} {:>20} {:>10} {:>10}",
+			"Bet", "Staked", "Returned", "RTP", "Exact RTP"
+		);
+		let (mut staked, mut returned, mut expected) = (0_u128, 0_u128, 0_f64);
+		for stats in &kinds {
+			println!(
+				"{:<12} {:>20} {:>20} {:>9.3}% {:>9.3}%",
+				format!("{:?}", stats.kind),
+				stats.staked,
+				stats.returned,
+				percentage(stats.returned as f64, stats.staked as f64),
+				percentage(stats.expected, stats.stake as f64),
+			);
+			staked += stats.staked;
+			returned += stats.returned;
+			expected += stats.expected;
+		}
+		println!(
+			"{:<12} {:>20} {:>20} {:>9.3}% {:>9.3}%",
+			"Total",
+			staked,
+			returned,
+			percentage(returned as f64, staked as f64),
+			percentage(expected, stakes as f64),
+		);
+		println!();
+		println!(
+			"House result per spin: mean {:.4}, variance {:.4}, standard deviation {:.4}.",
+			mean,
+			variance,
+			variance.sqrt()
+		);
+		println!("Largest payout of a spin: {}.", max_payout);
+		println!(
+			"Risk of ruin with a bankroll of {}: {:.2}% ({} of {} trials).",
+			self.bankroll,
+			percentage(ruined as f64, self.trials as f64),
+			ruined,
+			self.trials
+		);
+		println!("Bankroll for a risk of ruin of at most {}%: {}.", self.max_ruin, required);
+
+		Ok(())
+	}
+
+	/// Amount paid to the whole mix for an outcome.
+	fn payout(&self, outcome: u32) -> u128 {
+		self.bets
+			.iter()
+			.filter(|mixed| Roulette::is_winner(&mixed.bet, outcome))
+			.map(amount_won)
+			.sum()
+	}
+}
+
+/// Amount that a winning bet is paid, as computed by the lottery pallet.
+fn amount_won(mixed: &MixedBet) -> u128 {
+	mixed.stake.saturating_mul(u128::from(Roulette::payout_multiple(&mixed.bet)))
+}
+
+fn percentage(part: f64, whole: f64) -> f64 {
+	if whole == 0.0 {
+		0.0
+	} else {
+		part / whole * 100.0
+	}
+}
@@ -0,0 +1,190 @@
+//! Randomness beacon: games bound to beacon rounds wait for an offchain worker to fetch the round
+//! over HTTP and submit it in an unsigned transaction. Only the awaited rounds are accepted, and
+//! only when signed by the beacon key. `BeaconRandomness` hands the randomness of every game to the
+//! pallet once it is known, whether from the beacon or from a block hash.
+
+use crate::pallet::{
+	AwaitedBeaconOf, AwaitedBeaconRounds, BoundRandomness, Call, Config, Pallet, SourceOfRandomness,
+};
+use codec::Encode;
+use core::marker::PhantomData;
+use frame_support::{inherent::Vec, traits::Randomness};
+use frame_system::offchain::SubmitTransaction;
+use lite_json::JsonValue;
+use sp_core::{ed25519, H256};
+use sp_runtime::{
+	offchain::{http, storage::StorageValueRef, Duration},
+	traits::{Hash, Zero},
+};
+use traits::RandomnessSource;
+
+/// Key of the offchain local storage entry that overrides the URL of the beacon on a node.
+pub const BEACON_URL_KEY: &[u8] = b"lottery::beacon-url";
+
+/// Milliseconds that the offchain worker waits for the beacon to answer.
+const BEACON_TIMEOUT: u64 = 2_000;
+
+/// Message signed by the beacon for a round.
+pub fn beacon_message(round: u64, randomness: &H256) -> Vec<u8> {
+	(round, randomness).encode()
+}
+
+/// Randomness that every game is bound to, mixed with the subject of the game and known since the
+/// block in which it was submitted, for beacon rounds, or built, for block hashes. Games that are
+/// not bound yet, or whose randomness is not known yet, get a zero hash known since block zero.
+pub struct BeaconRandomness<T>(PhantomData<T>);
+
+impl<T: Config> Randomness<T::Hash, T::BlockNumber> for BeaconRandomness<T> {
+	fn random(subject: &[u8]) -> (T::Hash, T::BlockNumber) {
+		let known = BoundRandomness::<T>::get(T::Hashing::hash(subject))
+			.and_then(|request| Pallet::<T>::requested_randomness(&request));
+		match known {
+			Some((randomness, known_since)) =>
+				(T::Hashing::hash_of(&(randomness, subject)), known_since),
+			None => (T::Hash::default(), Zero::zero()),
+		}
+	}
+
+	/// Randomness of the last beacon round submitted that games are still bound to.
+	fn random_seed() -> (T::Hash, T::BlockNumber) {
+		let last = AwaitedBeaconRounds::<T>::iter_values()
+			.filter_map(|awaited| awaited.randomness)
+			.max_by_key(|(_, submitted_at)| *submitted_at);
+		match last {
+			Some((randomness, submitted_at)) => (T::Hashing::hash_of(&randomness), submitted_at),
+			None => (T::Hash::default(), Zero::zero()),
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Beacon round that games are bound to and that was not submitted yet.
+	pub(crate) fn awaited_beacon(round: u64) -> Option<AwaitedBeaconOf<T>> {
+		AwaitedBeaconRounds::<T>::get(round).filter(|awaited| awaited.randomness.is_none())
+	}
+
+	/// Whether `signature` was made by `public_key` over a beacon round.
+	pub(crate) fn verify_beacon(
+		public_key: [u8; 32],
+		round: u64,
+		randomness: &H256,
+		signature: &ed25519::Signature,
+	) -> bool {
+		let public = ed25519::Public::from_raw(public_key);
+		sp_io::crypto::ed25519_verify(signature, &beacon_message(round, randomness), &public)
+	}
+
+	/// Fetches and submits the beacon rounds that games are bound to and that should already be
+	/// published.
+	pub(crate) fn submit_awaited_beacons() -> Result<(), &'static str> {
+		let now = sp_io::offchain::timestamp().unix_millis() / 1_000;
+		let due = AwaitedBeaconRounds::<T>::iter()
+			.filter(|(_, awaited)| awaited.randomness.is_none() && awaited.published_at <= now)
+			.map(|(round, _)| round)
+			.collect::<Vec<_>>();
+		if due.is_empty() {
+			return Ok(())
+		}
+
+		let url = Self::beacon_url().ok_or("No beacon URL is set")?;
+		for round in due {
+			let (randomness, signature) = Self::fetch_beacon(&url, round)?;
+			let call = Call::submit_beacon { round, randomness, signature };
+			SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+				.map_err(|()| "Failed to submit beacon round")?;
+		}
+		Ok(())
+	}
+
+	/// URL of the beacon, taken from the offchain local storage if the node sets one.
+	fn beacon_url() -> Option<Vec<u8>> {
+		let url = StorageValueRef::persistent(BEACON_URL_KEY).get::<Vec<u8>>().ok().flatten();
+		url.or_else(|| match SourceOfRandomness::<T>::get()? {
+			RandomnessSource::Beacon(beacon) => Some(beacon.url.into_inner()),
+			RandomnessSource::BlockHash => None,
+		})
+	}
+
+	/// Requests a round to the beacon.
+	fn fetch_beacon(url: &[u8], round: u64) -> Result<(H256, ed25519::Signature), &'static str> {
+		let mut url = url.strip_suffix(b"/").unwrap_or(url).to_vec();
+		url.extend_from_slice(b"/public/");
+		url.extend_from_slice(&decimal(round));
+		let url = core::str::from_utf8(&url).map_err(|_| "Beacon URL is not valid UTF-8")?;
+
+		let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(BEACON_TIMEOUT));
+		let pending = http::Request::get(url)
+			.deadline(deadline)
+			.send()
+			.map_err(|_| "Failed to reach the beacon")?;
+		let response = pending
+			.try_wait(deadline)
+			.map_err(|_| "Beacon request timed out")?
+			.map_err(|_| "Beacon request failed")?;
+		if response.code != 200 {
+			return Err("Unexpected beacon response status")
+		}
+
+		let body = response.body().collect::<Vec<u8>>();
+		match Self::parse_beacon(&body) {
+			Some((answered, randomness, signature)) if answered == round =>
+				Ok((randomness, signature)),
+			Some(_) => Err("The beacon answered with another round"),
+			None => Err("Malformed beacon response"),
+		}
+	}
+
+	/// Parses a beacon round of the form
+	/// `{"round": 1, "randomness": "<32 bytes hex>", "signature": "<64 bytes hex>"}`.
+	pub(crate) fn parse_beacon(body: &[u8]) -> Option<(u64, H256, ed25519::Signature)> {
+		let body = core::str::from_utf8(body).ok()?;
+		let fields = match lite_json::parse_json(body).ok()? {
+			JsonValue::Object(fields) => fields,
+			_ => return None,
+		};
+		let field = |name: &str| {
+			fields
+				.iter()
+				.find(|(key, _)| key.iter().copied().eq(name.chars()))
+				.map(|(_, value)| value)
+		};
+
+		let round = match field("round")? {
+			JsonValue::Number(number) if !number.negative && number.fraction_length == 0 =>
+				number.integer,
+			_ => return None,
+		};
+		let randomness = H256(decode_hex(field("randomness")?)?);
+		let signature = ed25519::Signature(decode_hex(field("signature")?)?);
+
+		Some((round, randomness, signature))
+	}
+}
+
+/// Decodes a JSON string holding exactly `N` hex encoded bytes.
+fn decode_hex<const N: usize>(value: &JsonValue) -> Option<[u8; N]> {
+	let chars = match value {
+		JsonValue::String(chars) if chars.len() == 2 * N => chars,
+		_ => return None,
+	};
+
+	let mut bytes = [0_u8; N];
+	for (byte, pair) in bytes.iter_mut().zip(chars.chunks(2)) {
+		*byte = (pair[0].to_digit(16)? * 16 + pair[1].to_digit(16)?) as u8;
+	}
+	Some(bytes)
+}
+
+/// Decimal digits of a number, as ASCII.
+fn decimal(mut number: u64) -> Vec<u8> {
+	let mut digits = Vec::new();
+	loop {
+		digits.push(b'0' + (number % 10) as u8);
+		number /= 10;
+		if number == 0 {
+			break
+		}
+	}
+	digits.reverse();
+	digits
+}
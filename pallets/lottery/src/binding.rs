@@ -0,0 +1,105 @@
+//! Binding of the games to randomness: every roulette round and lottery draw is bound, once it
+//! stops taking bets, to randomness that nobody knows yet. That is the hash of a block built
+//! `RandomnessDelay` blocks later, or a beacon round published at least one beacon period later.
+//! Games are only played once the randomness they are bound to is known.
+//!
+//! The beacon round is picked from the timestamp of the block in which the game closes, so block
+//! authors that skew timestamps by more than a beacon period could bind a game to a round that
+//! is already public.
+
+use crate::pallet::{
+	AwaitedBeaconRounds, BoundRandomness, Config, Pallet, RandomnessRequestOf, RandomnessSourceOf,
+	SourceOfRandomness,
+};
+use codec::Decode;
+use frame_support::traits::UnixTime;
+use sp_core::H256;
+use sp_runtime::{traits::Hash, Saturating};
+use traits::{AwaitedBeacon, RandomnessRequest, RandomnessSource};
+
+impl<T: Config> Pallet<T> {
+	/// Binds a game that no longer takes bets at `now` to randomness that is not known yet: the
+	/// hash of the block built `RandomnessDelay` blocks later, or the beacon round after the next
+	/// one, which is published at least one beacon period later. Returns `None` while no
+	/// randomness source is set.
+	pub(crate) fn request_randomness(now: T::BlockNumber) -> Option<RandomnessRequestOf<T>> {
+		match SourceOfRandomness::<T>::get()? {
+			RandomnessSource::BlockHash =>
+				Some(RandomnessRequest::BlockHash(now.saturating_add(T::RandomnessDelay::get()))),
+			RandomnessSource::Beacon(beacon) => {
+				let round = beacon.round_at(T::UnixTime::now().as_secs()).saturating_add(2);
+				AwaitedBeaconRounds::<T>::mutate(round, |awaited| {
+					let awaited = awaited.get_or_insert_with(|| AwaitedBeacon {
+						public_key: beacon.public_key,
+						published_at: beacon.published_at(round),
+						games: 0,
+						randomness: None,
+					});
+					awaited.games = awaited.games.saturating_add(1);
+				});
+				Some(RandomnessRequest::Beacon(round))
+			},
+		}
+	}
+
+	/// Binds the game with `subject`, which no longer takes bets at `now`, to randomness that is
+	/// not known yet. Returns `None` while no randomness source is set.
+	pub(crate) fn bind_randomness(
+		subject: &[u8],
+		now: T::BlockNumber,
+	) -> Option<RandomnessRequestOf<T>> {
+		let request = Self::request_randomness(now)?;
+		BoundRandomness::<T>::insert(T::Hashing::hash(subject), request.clone());
+		Some(request)
+	}
+
+	/// Randomness that the game with `subject` is bound to.
+	pub(crate) fn bound_randomness(subject: &[u8]) -> Option<RandomnessRequestOf<T>> {
+		BoundRandomness::<T>::get(T::Hashing::hash(subject))
+	}
+
+	/// Whether the game with `subject` is bound to its randomness.
+	pub(crate) fn is_bound(subject: &[u8]) -> bool {
+		BoundRandomness::<T>::contains_key(T::Hashing::hash(subject))
+	}
+
+	/// Whether games can be bound to randomness from `source`.
+	pub(crate) fn is_valid_source(source: &RandomnessSourceOf<T>) -> bool {
+		match source {
+			RandomnessSource::Beacon(beacon) => beacon.period > 0,
+			RandomnessSource::BlockHash => true,
+		}
+	}
+
+	/// Randomness that a game is bound to and the block since which it is known, once it is.
+	pub(crate) fn requested_randomness(
+		request: &RandomnessRequestOf<T>,
+	) -> Option<(H256, T::BlockNumber)> {
+		match request {
+			RandomnessRequest::BlockHash(block) => {
+				if *block >= <frame_system::Pallet<T>>::block_number() {
+					return None
+				}
+				let hash = <frame_system::Pallet<T>>::block_hash(block);
+				Some((H256::decode(&mut hash.as_ref()).ok()?, *block))
+			},
+			RandomnessRequest::Beacon(round) => AwaitedBeaconRounds::<T>::get(round)?.randomness,
+		}
+	}
+
+	/// Lets go of the randomness of the game with `subject` once it is played. Beacon rounds are
+	/// removed once no game is bound to them.
+	pub(crate) fn release_randomness(subject: &[u8]) {
+		let request = BoundRandomness::<T>::take(T::Hashing::hash(subject));
+		if let Some(RandomnessRequest::Beacon(round)) = request {
+			AwaitedBeaconRounds::<T>::mutate_exists(round, |awaited| {
+				if let Some(beacon) = awaited {
+					beacon.games = beacon.games.saturating_sub(1);
+					if beacon.games == 0 {
+						*awaited = None;
+					}
+				}
+			});
+		}
+	}
+}
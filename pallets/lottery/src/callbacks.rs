@@ -0,0 +1,50 @@
+//! Callbacks of settled bets: owners that asked for it, such as contracts, are called back with
+//! the settlement of their bets. Callbacks wait in a queue and at most `MaxCallbacksPerBlock` of
+//! them are made in a block, so a round with many of them settles within the block weight.
+
+use crate::pallet::{CallbackQueue, CallbackQueueBounds, Config, Pallet};
+use frame_support::pallet_prelude::*;
+use sp_runtime::Saturating;
+use traits::{BetCallback, BetSettlement};
+
+impl<T: Config> Pallet<T> {
+	/// Queues a callback of `selector` on `who` with the settlement of one of its bets.
+	pub(crate) fn queue_callback(
+		who: T::AccountId,
+		selector: [u8; 4],
+		settlement: BetSettlement<T::Balance>,
+	) {
+		CallbackQueueBounds::<T>::mutate(|(_, next)| {
+			CallbackQueue::<T>::insert(*next, (who, selector, settlement));
+			*next = next.saturating_add(1);
+		});
+	}
+
+	/// Makes the first `MaxCallbacksPerBlock` queued callbacks, each spending at most
+	/// `CallbackWeight`.
+	pub(crate) fn make_callbacks() -> Weight {
+		let (mut first, next) = CallbackQueueBounds::<T>::get();
+		let mut weight = T::DbWeight::get().reads(1);
+		if first == next {
+			return weight
+		}
+
+		let last = next.min(first.saturating_add(u64::from(T::MaxCallbacksPerBlock::get())));
+		while first < last {
+			if let Some((who, selector, settlement)) = CallbackQueue::<T>::take(first) {
+				let spent = T::BetCallback::on_settled(
+					&who,
+					selector,
+					settlement,
+					T::CallbackWeight::get(),
+				);
+				weight = weight.saturating_add(spent.min(T::CallbackWeight::get()));
+			}
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			first = first.saturating_add(1);
+		}
+		CallbackQueueBounds::<T>::put((first, next));
+
+		weight.saturating_add(T::DbWeight::get().writes(1))
+	}
+}
@@ -0,0 +1,121 @@
+//! Transaction pool check for bets: `place_bet` calls that can never be played, that the sender
+//! cannot afford or that the house cannot cover are dropped from the pool before the sender is
+//! charged any fee. Bets only live in the pool while the round that would take them is open.
+
+use crate::pallet::{BetLimits, Call, Config, Exposure, Pallet, Paused, RoundEnds, RoundIndex};
+use codec::{Decode, Encode};
+use core::{fmt, marker::PhantomData};
+use frame_support::traits::{Get, IsSubType};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, One, SignedExtension, UniqueSaturatedInto},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	Saturating,
+};
+use traits::Game;
+
+/// Custom `InvalidTransaction` code of bets that cannot be played, because they are out of
+/// range, out of the stake limits or the lottery is paused.
+pub const INVALID_BET: u8 = 1;
+/// Custom `InvalidTransaction` code of bets that the sender cannot afford.
+pub const UNAFFORDABLE_BET: u8 = 2;
+/// Custom `InvalidTransaction` code of bets that the house cannot cover.
+pub const UNCOVERED_BET: u8 = 3;
+
+/// Signed extension that checks bets before they enter the transaction pool.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckBet<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckBet<T> {
+	/// Creates the extension.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckBet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for CheckBet<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckBet")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckBet<T>
+where
+	T::RuntimeCall: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckBet";
+	type AccountId = T::AccountId;
+	type Call = T::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		let (asset, amount, bet) = match call.is_sub_type() {
+			Some(Call::place_bet { asset, amount, bet }) => (*asset, *amount, bet),
+			_ => return Ok(ValidTransaction::default()),
+		};
+
+		let out_of_limits =
+			BetLimits::<T>::get(asset).map_or(false, |(min, max)| amount < min || amount > max);
+		if Paused::<T>::get() || !T::Game::validate(bet) || out_of_limits {
+			return InvalidTransaction::Custom(INVALID_BET).into()
+		}
+		if Pallet::<T>::reducible_balance(asset, who) < amount {
+			return InvalidTransaction::Custom(UNAFFORDABLE_BET).into()
+		}
+
+		// The house has to cover at least the current exposure of the round or the payout of
+		// this bet, whichever is higher. Hedging bets may lower the actual exposure, which is
+		// checked on dispatch, so only bets that certainly exceed the bankroll are dropped.
+		let round = RoundIndex::<T>::get();
+		let round_exposure = Exposure::<T>::get(asset, round);
+		let min_exposure = Pallet::<T>::total_exposure(asset)
+			.saturating_sub(round_exposure)
+			.saturating_add(round_exposure.max(Pallet::<T>::amount_won(bet, amount)));
+		if min_exposure > Pallet::<T>::house_balance(asset).saturating_add(amount) {
+			return InvalidTransaction::Custom(UNCOVERED_BET).into()
+		}
+
+		// Bets are dropped once the round they were sent for stops taking bets.
+		let now = <frame_system::Pallet<T>>::block_number();
+		let longevity = match RoundEnds::<T>::get(round) {
+			Some(ends_at) => ends_at.saturating_sub(now).saturating_add(One::one()),
+			None => T::BettingPeriod::get(),
+		};
+
+		Ok(ValidTransaction {
+			priority: T::BetPriority::get(),
+			longevity: longevity.unique_saturated_into(),
+			..Default::default()
+		})
+	}
+}
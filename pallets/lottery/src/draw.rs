@@ -0,0 +1,117 @@
+//! Draw-based lottery: players buy tickets with chosen numbers and, once the draw period is over,
+//! the winning numbers are picked and every prize tier is paid from the pot.
+
+use crate::pallet::{
+	Config, DrawEnd, DrawIndex, DrawResults, Error, Event, Pallet, Pot, TicketCount, Tickets,
+};
+use codec::Encode;
+use frame_support::{inherent::Vec, pallet_prelude::*};
+use sp_core::{hashing::blake2_256, H256};
+use sp_runtime::{
+	traits::{AccountIdConversion, Zero},
+	Saturating,
+};
+use traits::DrawResult;
+
+impl<T: Config> Pallet<T> {
+	/// Gets the account holding the lottery pot, kept apart from the roulette bankroll.
+	pub(crate) fn draw_account_id() -> T::AccountId {
+		T::PalletId::get().into_sub_account_truncating(b"draw")
+	}
+
+	/// Verifies that a ticket has `NumbersPerTicket` different numbers, all of them between 1
+	/// and `MaxTicketNumber`.
+	pub(crate) fn ensure_valid_ticket(numbers: &[u32]) -> DispatchResult {
+		ensure!(numbers.len() as u32 == T::NumbersPerTicket::get(), Error::<T>::InvalidTicket);
+		for (index, number) in numbers.iter().enumerate() {
+			ensure!(*number >= 1 && *number <= T::MaxTicketNumber::get(), Error::<T>::OutOfRange);
+			ensure!(!numbers[..index].contains(number), Error::<T>::InvalidTicket);
+		}
+		Ok(())
+	}
+
+	/// Subject of a draw, which its winning numbers are drawn from the `LotteryRandomness` of.
+	pub(crate) fn draw_subject(draw: u32) -> Vec<u8> {
+		(b"draw", draw).encode()
+	}
+
+	/// Derives `NumbersPerTicket` different winning numbers from a seed.
+	pub(crate) fn draw_numbers(seed: H256) -> Vec<u32> {
+		let amount = T::NumbersPerTicket::get() as usize;
+		let max_number = T::MaxTicketNumber::get();
+
+		let mut numbers = Vec::with_capacity(amount);
+		let mut nonce = 0_u32;
+		while numbers.len() < amount {
+			let hash = blake2_256(&(seed, nonce).encode());
+			let number = traits::sample_outcome(&hash, max_number) + 1;
+			if !numbers.contains(&number) {
+				numbers.push(number);
+			}
+			nonce += 1;
+		}
+		numbers
+	}
+
+	/// Picks the winning numbers of the current draw and pays every prize tier from the pot.
+	/// Whatever is not won stays in the pot for the next draw.
+	pub(crate) fn run_draw(now: T::BlockNumber, seed: H256) -> Weight {
+		let draw = DrawIndex::<T>::get();
+		let numbers = Self::draw_numbers(seed);
+		let numbers_per_ticket = T::NumbersPerTicket::get();
+		let tiers = T::PrizeTiers::get();
+		let pot = Pot::<T>::get();
+
+		// Winners of every prize tier, starting with the jackpot.
+		let mut winners: Vec<Vec<T::AccountId>> = tiers.iter().map(|_| Vec::new()).collect();
+		let mut tickets = 0_u32;
+		for ticket in Tickets::<T>::iter_prefix_values(draw) {
+			tickets += 1;
+			let matches = ticket.numbers.iter().filter(|number| numbers.contains(number)).count();
+			let tier = numbers_per_ticket.saturating_sub(matches as u32) as usize;
+			if let Some(tier_winners) = winners.get_mut(tier) {
+				tier_winners.push(ticket.owner);
+			}
+		}
+
+		// Split every tier equally between its winners.
+		let account_id = Self::draw_account_id();
+		let mut payout = T::Balance::zero();
+		for (tier, (share, tier_winners)) in tiers.iter().zip(winners.iter()).enumerate() {
+			if tier_winners.is_empty() {
+				continue
+			}
+			let prize = (*share * pot) / T::Balance::from(tier_winners.len() as u32);
+			for who in tier_winners {
+				if Self::transfer(T::NativeAssetId::get(), &account_id, who, prize).is_ok() {
+					payout = payout.saturating_add(prize);
+					Self::deposit_event(Event::PrizeAwarded {
+						draw,
+						who: who.clone(),
+						matches: numbers_per_ticket - tier as u32,
+						amount: prize,
+					});
+				}
+			}
+		}
+
+		let rollover = pot.saturating_sub(payout);
+		Pot::<T>::put(rollover);
+		if winners.first().map_or(true, |jackpot_winners| jackpot_winners.is_empty()) {
+			Self::deposit_event(Event::JackpotRolledOver { draw, amount: rollover });
+		}
+
+		let numbers = BoundedVec::truncate_from(numbers);
+		DrawResults::<T>::insert(
+			draw,
+			DrawResult { block: now, numbers: numbers.clone(), tickets, pot, payout },
+		);
+		Self::deposit_event(Event::DrawCompleted { draw, numbers, tickets, pot, payout });
+
+		// Following tickets go to the next draw.
+		DrawIndex::<T>::put(draw.wrapping_add(1));
+		DrawEnd::<T>::kill();
+
+		T::DbWeight::get().reads_writes(u64::from(tickets) + 3, u64::from(tickets) + 4)
+	}
+}
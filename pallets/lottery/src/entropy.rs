@@ -0,0 +1,59 @@
+//! Commit-reveal entropy: bettors of a round, or designated revealers, commit to a secret while
+//! the round takes bets and reveal it once the round closes. The spin seed mixes every reveal
+//! with the `LotteryRandomness` of the round, so no single party controls the outcome.
+//!
+//! A round with commitments is only bound to its randomness once every commitment is revealed or
+//! the reveal period is over, and no reveal is taken after that. The last revealer therefore
+//! decides whether to reveal without knowing the outcome either way, and withholding only costs
+//! its deposit. Parties that know the randomness early can still abort selectively: the beacon
+//! operator, which can compute rounds before publishing them, or block authors when the games
+//! are bound to block hashes.
+
+use crate::pallet::{
+	CommitmentDeposits, Config, EntropyCommitments, Event, OngoingBets, Pallet, Revealers,
+};
+use frame_support::inherent::Vec;
+use sp_core::H256;
+use sp_runtime::{traits::One, Saturating};
+
+impl<T: Config> Pallet<T> {
+	/// Whether `who` can commit entropy to `round`, either as one of its bettors or as a
+	/// designated revealer.
+	pub(crate) fn can_commit(round: u32, who: &T::AccountId) -> bool {
+		Revealers::<T>::contains_key(who) ||
+			OngoingBets::<T>::iter_prefix_values(round).any(|bet_data| &bet_data.owner == who)
+	}
+
+	/// Whether some commitment to a round can still be revealed.
+	pub(crate) fn reveals_open(round: u32, ends_at: T::BlockNumber, now: T::BlockNumber) -> bool {
+		let deadline = ends_at.saturating_add(One::one()).saturating_add(T::RevealPeriod::get());
+		now < deadline &&
+			EntropyCommitments::<T>::iter_prefix_values(round)
+				.any(|commitment| commitment.reveal.is_none())
+	}
+
+	/// Seed of a round, mixing `seed` with the entropy revealed for it. Deposits of commitments
+	/// that were not revealed are forfeited to the house.
+	pub(crate) fn round_seed(round: u32, seed: H256) -> H256 {
+		let mut reveals: Vec<H256> = Vec::new();
+		for (who, commitment) in EntropyCommitments::<T>::drain_prefix(round) {
+			match commitment.reveal {
+				Some(reveal) => reveals.push(reveal),
+				None => {
+					// The deposit is already held by the pallet account, it just stops being a
+					// liability of the house.
+					CommitmentDeposits::<T>::mutate(|deposits| {
+						*deposits = deposits.saturating_sub(commitment.deposit)
+					});
+					Self::deposit_event(Event::EntropyForfeited {
+						round,
+						who,
+						deposit: commitment.deposit,
+					});
+				},
+			}
+		}
+
+		traits::mix_entropy(seed, &reveals)
+	}
+}
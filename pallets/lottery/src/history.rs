@@ -0,0 +1,45 @@
+//! History of settled bets. Only a summary of every bet stays on chain, in `Bets`, while its full
+//! record is written to the offchain index, keyed by bet id and by account. Nodes started with
+//! `--enable-offchain-indexing` keep those records and serve them to history queries.
+
+use crate::pallet::{BetRecordOf, Bets, Config, Pallet, SettledBetCount};
+use codec::Encode;
+use frame_support::inherent::Vec;
+use sp_runtime::Saturating;
+use traits::SettledBet;
+
+/// Offchain index key of the record of a settled bet.
+pub fn bet_record_key(bet_id: u64) -> Vec<u8> {
+	(b"lottery/bet", bet_id).encode()
+}
+
+/// Offchain index key of the id of the `index`-th settled bet of `who`, counting from zero.
+pub fn account_bet_key<AccountId: Encode>(who: &AccountId, index: u32) -> Vec<u8> {
+	(b"lottery/bets-of", who, index).encode()
+}
+
+impl<T: Config> Pallet<T> {
+	/// Keeps the summary of a settled bet on chain and writes its full record to the offchain
+	/// index.
+	pub(crate) fn record_settled_bet(record: BetRecordOf<T>) {
+		let bet_id = record.bet.id;
+		let index = SettledBetCount::<T>::mutate(&record.bet.owner, |count| {
+			let index = *count;
+			*count = count.saturating_add(1);
+			index
+		});
+
+		sp_io::offchain_index::set(&bet_record_key(bet_id), &record.encode());
+		sp_io::offchain_index::set(&account_bet_key(&record.bet.owner, index), &bet_id.encode());
+
+		Bets::<T>::insert(
+			bet_id,
+			SettledBet {
+				round: record.round,
+				won: record.won,
+				payout: record.payout,
+				record_hash: record.hash(),
+			},
+		);
+	}
+}
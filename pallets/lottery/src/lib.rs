@@ -1,8 +1,25 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 // Re-export pallet items so that they can be accessed from the crate namespace.
+pub use beacon::{beacon_message, BeaconRandomness, BEACON_URL_KEY};
+pub use check_bet::{CheckBet, INVALID_BET, UNAFFORDABLE_BET, UNCOVERED_BET};
+pub use history::{account_bet_key, bet_record_key};
 pub use pallet::*;
 
+mod beacon;
+mod binding;
+mod callbacks;
+mod check_bet;
+mod draw;
+mod entropy;
+mod history;
+pub mod migrations;
+mod payouts;
+mod referrals;
+mod schedules;
+mod strategies;
+mod vouchers;
+
 #[cfg(test)]
 mod mock;
 
@@ -11,32 +28,131 @@ mod tests;
 
 #[frame_support::pallet]
 pub mod pallet {
+	use crate::referrals::ReferredResultsOf;
 	use codec::Codec;
-	use frame_support::traits::{
-		fungible::{Inspect, Mutate, Transfer},
-		LockIdentifier, LockableCurrency, Randomness, WithdrawReasons,
+	use frame_support::{
+		inherent::Vec,
+		pallet_prelude::*,
+		storage::{with_transaction, TransactionOutcome},
+		traits::{fungible, fungibles, Randomness, UnixTime},
+		PalletId,
 	};
-	use frame_support::{inherent::Vec, pallet_prelude::*, PalletId};
-	use frame_system::pallet_prelude::*;
-	use sp_core::H256;
+	use frame_system::{offchain::SendTransactionTypes, pallet_prelude::*};
+	use sp_core::{ed25519, hashing::blake2_256, H256};
 	use sp_runtime::{
 		traits::{
-			AccountIdConversion, AtLeast32BitUnsigned, CheckedAdd, CheckedMul, CheckedSub, Zero,
+			AccountIdConversion, AtLeast32BitUnsigned, CheckedAdd, CheckedMul, CheckedSub, Hash,
+			One, Zero,
 		},
-		Saturating,
+		Perbill, Saturating,
+	};
+	use traits::{
+		AwaitedBeacon, BeaconInfo, BetCallback, BetData, BetRecord, BetSettlement, Derivation,
+		DrawResult, EntropyCommitment, Game, PendingPayout, Progression, RandomnessRequest,
+		RandomnessSource, ReferralStats, ReferrerInfo, RoundTotals, ScheduledBet, SettledBet,
+		SpinRecord, StopReason, Strategy, Ticket, Voucher,
 	};
-	use traits::{Bet, BetData, DozenOrColumn, Half, OddOrEven, RouletteColor, RouletteNumber};
 
-	// The LockIdentifier constant.
-	const PALLET_ID: LockIdentifier = *b"roulette";
+	/// What players bet on in the game played by this pallet.
+	pub type PickOf<T> = <<T as Config>::Game as Game>::Pick;
+
+	/// Kind of a pick in the game played by this pallet.
+	pub type KindOf<T> = <<T as Config>::Game as Game>::Kind;
+
+	/// Bet data as stored by this pallet.
+	pub type BetDataOf<T> = BetData<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::AssetId,
+		<T as frame_system::Config>::BlockNumber,
+		<T as Config>::Balance,
+		PickOf<T>,
+	>;
+
+	/// Full record of a settled bet, as written to the offchain index by this pallet.
+	pub type BetRecordOf<T> = BetRecord<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::AssetId,
+		<T as frame_system::Config>::BlockNumber,
+		<T as Config>::Balance,
+		PickOf<T>,
+	>;
+
+	/// Summary of a settled bet as stored by this pallet.
+	pub type SettledBetOf<T> = SettledBet<<T as Config>::Balance>;
+
+	/// Numbers chosen in a ticket of the draw-based lottery.
+	pub type TicketNumbersOf<T> = BoundedVec<u32, <T as Config>::NumbersPerTicket>;
+
+	/// Ticket of the draw-based lottery as stored by this pallet.
+	pub type TicketOf<T> = Ticket<<T as frame_system::Config>::AccountId, TicketNumbersOf<T>>;
+
+	/// Result of a lottery draw as stored by this pallet.
+	pub type DrawResultOf<T> = DrawResult<
+		<T as frame_system::Config>::BlockNumber,
+		TicketNumbersOf<T>,
+		<T as Config>::Balance,
+	>;
+
+	/// Code with which players bind to a referrer.
+	pub type ReferralCodeOf<T> = BoundedVec<u8, <T as Config>::MaxReferralCodeLength>;
+
+	/// Standing order of a player as stored by this pallet.
+	pub type ScheduledBetOf<T> = ScheduledBet<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::AssetId,
+		<T as Config>::Balance,
+		PickOf<T>,
+	>;
+
+	/// Betting strategy as stored by this pallet.
+	pub type StrategyOf<T> = Strategy<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::AssetId,
+		<T as Config>::Balance,
+		PickOf<T>,
+	>;
+
+	/// Kinds of bets that a voucher can be used for.
+	pub type VoucherBetKindsOf<T> = BoundedVec<KindOf<T>, <T as Config>::MaxVoucherBetKinds>;
+
+	/// Where the randomness of the games comes from, as stored by this pallet.
+	pub type RandomnessSourceOf<T> =
+		RandomnessSource<BoundedVec<u8, <T as Config>::MaxBeaconUrlLength>>;
+
+	/// Randomness that a game is bound to, as stored by this pallet.
+	pub type RandomnessRequestOf<T> = RandomnessRequest<<T as frame_system::Config>::BlockNumber>;
+
+	/// Beacon round that games are bound to, as stored by this pallet.
+	pub type AwaitedBeaconOf<T> = AwaitedBeacon<<T as frame_system::Config>::BlockNumber>;
+
+	/// Free bet voucher as stored by this pallet.
+	pub type VoucherOf<T> = Voucher<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::Balance,
+		<T as frame_system::Config>::BlockNumber,
+		VoucherBetKindsOf<T>,
+	>;
+
+	/// Totals of the bets of a round settled in a block, for a single asset.
+	struct SpinTotals<AssetId, Balance> {
+		asset: AssetId,
+		players: u32,
+		staked: Balance,
+		income: Balance,
+		payout: Balance,
+	}
+
+	/// Version of the storage layout, bumped by every migration in `crate::migrations`.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: frame_system::Config + SendTransactionTypes<Call<Self>> {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
@@ -54,15 +170,188 @@ pub mod pallet {
 			+ MaxEncodedLen
 			+ Zero;
 
-		type LotteryRandomness: Randomness<H256, u32>;
+		/// Identifier of the assets that can be used to place bets.
+		type AssetId: Member + Parameter + Copy + MaybeSerializeDeserialize + MaxEncodedLen;
+
+		/// Randomness that the games are played with, normally `BeaconRandomness<Self>`. It is
+		/// asked for the subject of a game once the game is bound to randomness, and the game is
+		/// only played with randomness that was not known while it took bets or tickets.
+		type LotteryRandomness: Randomness<Self::Hash, Self::BlockNumber>;
+
+		/// The game played with the bets, such as `traits::Roulette`.
+		type Game: Game;
+
+		/// The native currency, used for bets placed in `NativeAssetId`.
+		type Currency: fungible::Inspect<Self::AccountId, Balance = Self::Balance>
+			+ fungible::Transfer<Self::AccountId, Balance = Self::Balance>
+			+ fungible::Mutate<Self::AccountId>;
+
+		/// Any other asset that bets can be placed in.
+		type Assets: fungibles::Inspect<Self::AccountId, AssetId = Self::AssetId, Balance = Self::Balance>
+			+ fungibles::Transfer<Self::AccountId>
+			+ fungibles::Mutate<Self::AccountId>;
 
-		type Currency: Inspect<Self::AccountId, Balance = Self::Balance>
-			+ Transfer<Self::AccountId, Balance = Self::Balance>
-			+ Mutate<Self::AccountId>
-			+ LockableCurrency<Self::AccountId, Balance = Self::Balance>;
+		/// Asset id that stands for the native currency. Bets placed in this asset are handled
+		/// by `Currency` instead of `Assets`.
+		#[pallet::constant]
+		type NativeAssetId: Get<Self::AssetId>;
 
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
+
+		/// Price of a lottery ticket, paid in the native currency.
+		#[pallet::constant]
+		type TicketPrice: Get<Self::Balance>;
+
+		/// Amount of numbers that have to be chosen in every ticket.
+		#[pallet::constant]
+		type NumbersPerTicket: Get<u32>;
+
+		/// Highest number that can be chosen in a ticket. Numbers start at 1.
+		#[pallet::constant]
+		type MaxTicketNumber: Get<u32>;
+
+		/// Blocks between the first ticket of a draw being bought and the draw closing its ticket
+		/// sales, after which it is bound to randomness and takes place once that is known.
+		#[pallet::constant]
+		type DrawPeriod: Get<Self::BlockNumber>;
+
+		/// Maximum amount of tickets that can take part in a single draw.
+		#[pallet::constant]
+		type MaxTicketsPerDraw: Get<u32>;
+
+		/// Share of the pot paid to every prize tier. The first tier is the jackpot, won by
+		/// matching all numbers, the second one is won by matching all numbers but one, and so on.
+		/// Winners of a tier split its share equally.
+		#[pallet::constant]
+		type PrizeTiers: Get<Vec<Perbill>>;
+
+		/// Share of every roulette stake that flows into the progressive jackpot of its asset.
+		/// The jackpot is disabled when zero.
+		#[pallet::constant]
+		type JackpotContribution: Get<Perbill>;
+
+		/// Share of the progressive jackpot paid to its winner. The rest seeds the next jackpot.
+		#[pallet::constant]
+		type JackpotPayout: Get<Perbill>;
+
+		/// Consecutive spins in which a player has to hit the same single-outcome pick, such as a
+		/// straight-up number in roulette, with bets in the same asset to win the progressive
+		/// jackpot of that asset.
+		#[pallet::constant]
+		type JackpotStreak: Get<u32>;
+
+		/// Blocks during which a roulette round takes bets, starting with the block of its first
+		/// bet.
+		#[pallet::constant]
+		type BettingPeriod: Get<Self::BlockNumber>;
+
+		/// Blocks between a game being bound to randomness and the block whose hash it is bound
+		/// to, when games are bound to block hashes. Games are played once that block is built,
+		/// so no bet or ticket can be placed knowing the hash.
+		#[pallet::constant]
+		type RandomnessDelay: Get<Self::BlockNumber>;
+
+		/// Deposit in the native currency taken from whoever commits entropy to a round. It is
+		/// returned on reveal and forfeited to the house otherwise.
+		#[pallet::constant]
+		type CommitmentDeposit: Get<Self::Balance>;
+
+		/// Blocks after a round closes during which committed entropy can still be revealed.
+		/// Rounds with commitments are bound to their randomness once every commitment is
+		/// revealed or this period is over.
+		#[pallet::constant]
+		type RevealPeriod: Get<Self::BlockNumber>;
+
+		/// Maximum amount of entropy commitments per round.
+		#[pallet::constant]
+		type MaxCommitments: Get<u32>;
+
+		/// Time from which games pick the beacon round they are bound to.
+		type UnixTime: UnixTime;
+
+		/// Maximum length of the URL of the randomness beacon.
+		#[pallet::constant]
+		type MaxBeaconUrlLength: Get<u32>;
+
+		/// Priority of the unsigned transactions that submit beacon rounds.
+		#[pallet::constant]
+		type BeaconUnsignedPriority: Get<TransactionPriority>;
+
+		/// Priority of the transactions that place bets, given by `CheckBet`.
+		#[pallet::constant]
+		type BetPriority: Get<TransactionPriority>;
+
+		/// Whether winnings are credited to `PendingPayouts` for players to claim, instead of
+		/// being transferred to them on settlement.
+		#[pallet::constant]
+		type PullPayouts: Get<bool>;
+
+		/// Blocks during which credited winnings can be claimed before going back to the house.
+		#[pallet::constant]
+		type ClaimPeriod: Get<Self::BlockNumber>;
+
+		/// Maximum amount of credited winnings returned to the house in a block. Winnings beyond
+		/// it expire in the next blocks.
+		#[pallet::constant]
+		type MaxExpiriesPerBlock: Get<u32>;
+
+		/// Share of the net income of the house from a referred player, computed on every
+		/// settlement, that accrues to its referrer.
+		#[pallet::constant]
+		type ReferralShare: Get<Perbill>;
+
+		/// Maximum length of a referral code.
+		#[pallet::constant]
+		type MaxReferralCodeLength: Get<u32>;
+
+		/// Origin allowed to administer the lottery: pausing it, limiting stakes, setting the
+		/// payout table, withdrawing house balance, allowing revealers and issuing vouchers.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Maximum amount of bet kinds that a voucher can be restricted to.
+		#[pallet::constant]
+		type MaxVoucherBetKinds: Get<u32>;
+
+		/// Maximum amount of vouchers that can expire in the same block, so that pruning them
+		/// is bounded.
+		#[pallet::constant]
+		type MaxVoucherExpiries: Get<u32>;
+
+		/// Maximum amount of scheduled bets, placed at the start of every round.
+		#[pallet::constant]
+		type MaxScheduledBets: Get<u32>;
+
+		/// Maximum amount of betting strategies being played.
+		#[pallet::constant]
+		type MaxStrategies: Get<u32>;
+
+		/// Calls back the accounts whose bets asked for it, such as contracts, on settlement.
+		type BetCallback: BetCallback<Self::AccountId, Self::Balance>;
+
+		/// Maximum weight that a single callback can spend.
+		#[pallet::constant]
+		type CallbackWeight: Get<Weight>;
+
+		/// Fee paid to the house in the native currency by every bet that asks to be called
+		/// back, for the weight of its callback.
+		#[pallet::constant]
+		type CallbackFee: Get<Self::Balance>;
+
+		/// Maximum amount of callbacks made in a block. Callbacks beyond it wait for the next
+		/// blocks.
+		#[pallet::constant]
+		type MaxCallbacksPerBlock: Get<u32>;
+
+		/// Maximum amount of rounds played in a block. Rounds beyond it wait for the next
+		/// blocks.
+		#[pallet::constant]
+		type MaxRoundsPerBlock: Get<u32>;
+
+		/// Maximum amount of bets settled in a block, over every round played in it. A round with
+		/// more bets is spun once and settled over the next blocks.
+		#[pallet::constant]
+		type MaxBetsPerBlock: Get<u32>;
 	}
 
 	// Pallets use events to inform users when important changes are made.
@@ -71,15 +360,153 @@ pub mod pallet {
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		/// Event emitted when a bet has been placed
-		BetPlaced { bet_id: u64, who: T::AccountId, bet: Bet, amount: T::Balance },
-		/// Event emitted when a game is played.
+		BetPlaced {
+			round: u32,
+			bet_id: u64,
+			who: T::AccountId,
+			bet: PickOf<T>,
+			asset: T::AssetId,
+			amount: T::Balance,
+		},
+		/// Event emitted when a bet is settled. The owner of the bet is an indexed topic of the
+		/// event, so wallets can look for their own bets. The payout of `pending` bets is
+		/// credited to `PendingPayouts` instead of paid, either because players pull their
+		/// winnings or because paying them failed.
+		BetSettled { bet_id: u64, who: T::AccountId, won: bool, payout: T::Balance, pending: bool },
+		/// Event emitted when a bet cannot be settled and is dropped. The owner of the bet is an
+		/// indexed topic of the event.
+		BetRejected { bet_id: u64, who: T::AccountId, error: DispatchError },
+		/// Event emitted when a round stops taking bets. It is played once its randomness is
+		/// available.
+		RoundClosed { round: u32, ends_at: T::BlockNumber },
+		/// Event emitted when a game is played, once for every asset that had bets on the table.
+		/// The winner number can be recomputed from the seed with `traits::verify_outcome`.
 		RoulettePlayed {
+			round: u32,
+			asset: T::AssetId,
+			seed: H256,
 			winner_number: u32,
-			winner_color: Option<RouletteColor>,
 			players: u32,
 			income: T::Balance,
 			payout: T::Balance,
 		},
+		/// Event emitted when a lottery ticket has been bought.
+		TicketBought { draw: u32, ticket: u32, who: T::AccountId, numbers: TicketNumbersOf<T> },
+		/// Event emitted when a lottery draw takes place.
+		DrawCompleted {
+			draw: u32,
+			numbers: TicketNumbersOf<T>,
+			tickets: u32,
+			pot: T::Balance,
+			payout: T::Balance,
+		},
+		/// Event emitted when a ticket wins a prize in a lottery draw.
+		PrizeAwarded { draw: u32, who: T::AccountId, matches: u32, amount: T::Balance },
+		/// Event emitted when nobody wins the jackpot of a draw, so the pot carries over.
+		JackpotRolledOver { draw: u32, amount: T::Balance },
+		/// Event emitted when roulette stakes flow into the progressive jackpot of an asset.
+		JackpotIncreased { asset: T::AssetId, contribution: T::Balance, jackpot: T::Balance },
+		/// Event emitted when a player wins the progressive jackpot of an asset.
+		JackpotWon { asset: T::AssetId, who: T::AccountId, number: u32, amount: T::Balance },
+		/// Event emitted when a player commits to the entropy of a round.
+		EntropyCommitted { round: u32, who: T::AccountId, commitment: H256 },
+		/// Event emitted when committed entropy is revealed. The seed of the round mixes every
+		/// reveal with `traits::mix_entropy`.
+		EntropyRevealed { round: u32, who: T::AccountId, entropy: H256 },
+		/// Event emitted when committed entropy is not revealed in time and its deposit is lost.
+		EntropyForfeited { round: u32, who: T::AccountId, deposit: T::Balance },
+		/// Event emitted when an account is allowed or no longer allowed to commit entropy to
+		/// rounds it does not bet on.
+		RevealerSet { who: T::AccountId, allowed: bool },
+		/// Event emitted when a round of the randomness beacon that games are bound to is
+		/// submitted.
+		BeaconRoundSubmitted { round: u64, randomness: H256 },
+		/// Event emitted when the source of the randomness of the games changes. Games that
+		/// already stopped taking bets keep the randomness they are bound to.
+		RandomnessSourceSet { source: RandomnessSourceOf<T> },
+		/// Event emitted when winnings are credited to a player, with the total that it can claim.
+		PayoutCredited {
+			who: T::AccountId,
+			asset: T::AssetId,
+			amount: T::Balance,
+			pending: T::Balance,
+			expires_at: T::BlockNumber,
+		},
+		/// Event emitted when credited winnings are paid out.
+		WinningsClaimed { who: T::AccountId, asset: T::AssetId, amount: T::Balance },
+		/// Event emitted when credited winnings go back to the house unclaimed.
+		PayoutExpired { who: T::AccountId, asset: T::AssetId, amount: T::Balance },
+		/// Event emitted when an account registers as a referrer.
+		ReferrerRegistered { who: T::AccountId, code: ReferralCodeOf<T> },
+		/// Event emitted when a player binds to a referrer.
+		ReferrerBound { who: T::AccountId, referrer: T::AccountId },
+		/// Event emitted when a referrer earns a share of what the house won from a player in a
+		/// round.
+		ReferralRewarded {
+			round: u32,
+			referrer: T::AccountId,
+			player: T::AccountId,
+			asset: T::AssetId,
+			reward: T::Balance,
+		},
+		/// Event emitted when a referrer claims its rewards.
+		ReferralRewardsClaimed { who: T::AccountId, asset: T::AssetId, amount: T::Balance },
+		/// Event emitted when a free bet voucher is issued to a player.
+		VoucherIssued {
+			voucher_id: u64,
+			who: T::AccountId,
+			amount: T::Balance,
+			expires_at: T::BlockNumber,
+		},
+		/// Event emitted when a voucher is used to place a bet.
+		VoucherRedeemed { voucher_id: u64, bet_id: u64 },
+		/// Event emitted when a voucher that was not used is removed after it expires.
+		VoucherExpired { voucher_id: u64 },
+		/// Event emitted when a player schedules a bet for the upcoming rounds.
+		BetScheduled {
+			schedule_id: u64,
+			who: T::AccountId,
+			asset: T::AssetId,
+			amount: T::Balance,
+			bet: PickOf<T>,
+			rounds: u32,
+			budget: T::Balance,
+		},
+		/// Event emitted when a scheduled bet cannot be placed. Its schedule ends.
+		ScheduledBetFailed { schedule_id: u64, error: DispatchError },
+		/// Event emitted when a scheduled bet is no longer placed, because it ran out of rounds
+		/// or budget, failed, or was cancelled.
+		ScheduleEnded { schedule_id: u64 },
+		/// Event emitted when a player starts a betting strategy.
+		StrategyRegistered {
+			strategy_id: u64,
+			who: T::AccountId,
+			asset: T::AssetId,
+			bet: PickOf<T>,
+			base_stake: T::Balance,
+			progression: Progression,
+		},
+		/// Event emitted when a strategy places a bet, at the given step of its progression.
+		StrategyStep { strategy_id: u64, bet_id: u64, step: u32, stake: T::Balance },
+		/// Event emitted when the next bet of a strategy cannot be placed.
+		StrategyBetFailed { strategy_id: u64, error: DispatchError },
+		/// Event emitted when a strategy stops placing bets.
+		StrategyStopped {
+			strategy_id: u64,
+			reason: StopReason,
+			staked: T::Balance,
+			paid: T::Balance,
+		},
+		/// Event emitted when the lottery stops or resumes taking bets and tickets.
+		PausedSet { paused: bool },
+		/// Event emitted when the stakes that bets in an asset can have are limited, or no
+		/// longer limited.
+		BetLimitsSet { asset: T::AssetId, limits: Option<(T::Balance, T::Balance)> },
+		/// Event emitted when the payout multiple of a kind of bet is overridden, or reset to
+		/// the one of the game.
+		PayoutMultipleSet { kind: KindOf<T>, multiple: Option<u32> },
+		/// Event emitted when free house balance is withdrawn.
+		HouseWithdrawn { asset: T::AssetId, amount: T::Balance, dest: T::AccountId },
 	}
 
 	#[pallet::error]
@@ -90,6 +517,87 @@ pub mod pallet {
 		NotEnoughBalanceInPalletAccount,
 		/// Number must be between 0 and 36.
 		OutOfRange,
+		/// Ticket does not have the right amount of numbers, or has repeated numbers.
+		InvalidTicket,
+		/// The current draw cannot take more tickets.
+		TooManyTickets,
+		/// The period of the current draw is over and it waits for its randomness.
+		DrawClosed,
+		/// The current round is not taking bets.
+		RoundNotOpen,
+		/// Only bettors of the round and designated revealers can commit entropy.
+		NotAllowedToCommit,
+		/// Entropy was already committed to this round.
+		AlreadyCommitted,
+		/// The round cannot take more entropy commitments.
+		TooManyCommitments,
+		/// Entropy can only be revealed after the round closes and before it is bound to its
+		/// randomness.
+		RevealNotOpen,
+		/// No entropy was committed to this round.
+		NoCommitment,
+		/// The committed entropy was already revealed.
+		AlreadyRevealed,
+		/// The revealed entropy does not match the commitment.
+		InvalidReveal,
+		/// No game is bound to the beacon round, or it was already submitted.
+		UnexpectedBeaconRound,
+		/// The beacon round is not signed by the beacon key.
+		InvalidBeaconSignature,
+		/// There are no winnings to claim.
+		NothingToClaim,
+		/// The referral code is already in use.
+		ReferralCodeTaken,
+		/// The account is already a referrer.
+		AlreadyReferrer,
+		/// No referrer uses this referral code.
+		UnknownReferralCode,
+		/// The player is already bound to a referrer.
+		AlreadyReferred,
+		/// Players cannot refer themselves.
+		SelfReferral,
+		/// No voucher has this id.
+		UnknownVoucher,
+		/// The voucher was issued to another account.
+		NotVoucherOwner,
+		/// The voucher can no longer be used.
+		VoucherExpired,
+		/// The voucher cannot be used for this kind of bet.
+		BetKindNotAllowed,
+		/// A voucher needs a stake and to expire after the current block.
+		InvalidVoucher,
+		/// No more vouchers can expire in that block.
+		TooManyVoucherExpiries,
+		/// A scheduled bet needs at least a round and a budget that covers it.
+		InvalidSchedule,
+		/// No more bets can be scheduled.
+		TooManyScheduledBets,
+		/// No scheduled bet has this id.
+		UnknownSchedule,
+		/// The scheduled bet belongs to another account.
+		NotScheduleOwner,
+		/// A strategy needs a base stake that its stop-loss covers, and a take-profit.
+		InvalidStrategy,
+		/// No more strategies can be played.
+		TooManyStrategies,
+		/// No strategy has this id.
+		UnknownStrategy,
+		/// The strategy belongs to another account.
+		NotStrategyOwner,
+		/// The lottery is paused and does not take bets or tickets.
+		Paused,
+		/// The stake is below the minimum of the asset.
+		BetTooSmall,
+		/// The stake is above the maximum of the asset.
+		BetTooLarge,
+		/// The minimum stake cannot be above the maximum.
+		InvalidBetLimits,
+		/// A payout multiple must at least pay the stake back.
+		InvalidPayoutMultiple,
+		/// The house cannot withdraw balance that backs bets in play.
+		NotEnoughFreeHouseBalance,
+		/// A randomness beacon needs a period.
+		InvalidRandomnessSource,
 	}
 
 	#[pallet::type_value]
@@ -100,254 +608,1642 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type BetNonce<T: Config> = StorageValue<_, u64, ValueQuery, DefaultBetNonce<T>>;
 
+	/// Summary of every settled bet. Full records are in the offchain index.
+	#[pallet::storage]
+	pub(super) type Bets<T: Config> = StorageMap<_, Blake2_128Concat, u64, SettledBetOf<T>>;
+
+	/// Amount of bets of every account that were settled, which numbers their offchain records.
 	#[pallet::storage]
-	pub(super) type Bets<T: Config> =
-		StorageMap<_, Blake2_128Concat, u64, BetData<T::AccountId, T::BlockNumber, T::Balance>>;
+	pub(super) type SettledBetCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
 
+	/// Bets that have not been settled yet, by round and bet id.
 	#[pallet::storage]
 	pub(super) type OngoingBets<T: Config> =
-		StorageMap<_, Blake2_128Concat, u64, BetData<T::AccountId, T::BlockNumber, T::Balance>>;
+		StorageDoubleMap<_, Blake2_128Concat, u32, Blake2_128Concat, u64, BetDataOf<T>>;
 
-	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		fn on_initialize(_: T::BlockNumber) -> Weight {
-			// Do not play if there are no active bets.
-			let players = OngoingBets::<T>::iter_keys().fold(0_u32, |acc, _| acc + 1_u32);
-			if players == 0_u32 {
-				return T::DbWeight::get().reads(1);
-			}
+	/// Round of every bet that has not been settled yet, by owner and bet id.
+	#[pallet::storage]
+	pub(super) type OngoingBetsByOwner<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, u64, u32>;
 
-			// Pallet account.
-			let account_id = Self::account_id();
+	/// Amount of bets that have not been settled yet, by round.
+	#[pallet::storage]
+	pub(super) type RoundBetCounts<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, u32, ValueQuery>;
 
-			// Unlock pallet funds.
-			T::Currency::remove_lock(PALLET_ID, &account_id);
+	/// Amount that the house can lose in the worst case scenario with the ongoing bets, per asset
+	/// and round.
+	#[pallet::storage]
+	pub(super) type Exposure<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AssetId,
+		Blake2_128Concat,
+		u32,
+		T::Balance,
+		ValueQuery,
+	>;
 
-			// Get random roulette number.
-			let winner_number = Self::random_number();
+	/// Index of the roulette round that is currently taking bets.
+	#[pallet::storage]
+	pub(super) type RoundIndex<T: Config> = StorageValue<_, u32, ValueQuery>;
 
-			// Send or receive balance based on bet results, keeping track of totals.
-			let (total_income, total_payout) = OngoingBets::<T>::iter().fold(
-				(T::Balance::default(), T::Balance::default()),
-				|(mut acc_income, mut acc_payout), (bet_id, bet_data)| {
-					// Unlock account funds.
-					T::Currency::remove_lock(PALLET_ID, &bet_data.owner);
+	/// Last block in which a round takes bets, set when its first bet is placed and removed once
+	/// the round is played.
+	#[pallet::storage]
+	pub(super) type RoundEnds<T: Config> = StorageMap<_, Blake2_128Concat, u32, T::BlockNumber>;
 
-					let is_winner = Self::is_winner(bet_data.bet.clone(), winner_number);
+	/// Oldest round that has not been played yet.
+	#[pallet::storage]
+	pub(super) type NextRoundToPlay<T: Config> = StorageValue<_, u32, ValueQuery>;
 
-					if is_winner {
-						// Transfer prize to account.
-						let payout_amount = Self::amount_won(bet_data.bet.clone(), bet_data.amount);
+	/// Entropy committed to every round that has not been played yet, by round and account.
+	#[pallet::storage]
+	pub(super) type EntropyCommitments<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		u32,
+		Blake2_128Concat,
+		T::AccountId,
+		EntropyCommitment<T::Balance>,
+	>;
 
-						acc_payout = acc_payout.saturating_add(payout_amount);
+	/// Commitment deposits held by the pallet account. They are not part of the house bankroll.
+	#[pallet::storage]
+	pub(super) type CommitmentDeposits<T: Config> = StorageValue<_, T::Balance, ValueQuery>;
 
-						let _ = T::Currency::transfer(
-							&account_id,
-							&bet_data.owner,
-							payout_amount,
-							true,
-						);
-					} else {
-						// Charge amount to account.
-						acc_income = acc_income.saturating_add(bet_data.amount);
+	/// Accounts that can commit entropy to rounds without betting on them.
+	#[pallet::storage]
+	pub(super) type Revealers<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
 
-						let _ = T::Currency::transfer(
-							&bet_data.owner,
-							&account_id,
-							bet_data.amount,
-							true,
-						);
-					}
+	/// Where the randomness of the games comes from. Games are not played while it is unset.
+	#[pallet::storage]
+	pub(super) type SourceOfRandomness<T: Config> = StorageValue<_, RandomnessSourceOf<T>>;
 
-					// Copy bet to history storage.
-					Bets::<T>::insert(bet_id, bet_data);
-					// Remove bet from ongoing bets.
-					OngoingBets::<T>::remove(bet_id);
+	/// Randomness that every game no longer taking bets or tickets is bound to, by the hash of
+	/// the subject of the game. Removed once the game is played.
+	#[pallet::storage]
+	pub(super) type BoundRandomness<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::Hash, RandomnessRequestOf<T>>;
 
-					(acc_income, acc_payout)
-				},
-			);
+	/// Beacon rounds that games are bound to, with their randomness once submitted. They are
+	/// removed once every game bound to them is played.
+	#[pallet::storage]
+	pub(super) type AwaitedBeaconRounds<T: Config> =
+		StorageMap<_, Blake2_128Concat, u64, AwaitedBeaconOf<T>>;
 
-			Self::deposit_event(Event::RoulettePlayed {
-				winner_number,
-				winner_color: winner_number.to_color(),
-				players,
-				income: total_income,
-				payout: total_payout,
-			});
+	/// Winnings credited to every player, per asset, waiting to be claimed.
+	#[pallet::storage]
+	pub(super) type PendingPayouts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AssetId,
+		PendingPayout<T::Balance, T::BlockNumber>,
+	>;
 
-			T::DbWeight::get().reads(1) + T::DbWeight::get().writes(1)
-		}
-	}
+	/// Total of the winnings waiting to be claimed, per asset. They are held by the pallet
+	/// account but are not part of the house bankroll.
+	#[pallet::storage]
+	pub(super) type PendingTotals<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, T::Balance, ValueQuery>;
 
-	#[pallet::call]
-	impl<T: Config> Pallet<T> {
-		#[pallet::weight(0)]
-		pub fn place_bet(origin: OriginFor<T>, amount: T::Balance, bet: Bet) -> DispatchResult {
-			// Check that the extrinsic was signed and get the signer.
-			// This function will return an error if the extrinsic is not signed.
-			let sender = ensure_signed(origin)?;
+	/// Referrer that registered every referral code.
+	#[pallet::storage]
+	pub(super) type ReferralCodes<T: Config> =
+		StorageMap<_, Blake2_128Concat, ReferralCodeOf<T>, T::AccountId>;
 
-			// Verify that the buyer has enough balance to afford the bet and is
-			// left with more than the existential deposit.
-			let reducible_balance = T::Currency::reducible_balance(&sender, true);
-			ensure!(reducible_balance >= amount, Error::<T>::NotEnoughBalance);
+	/// Code of every referrer and how many players it brought in.
+	#[pallet::storage]
+	pub(super) type ReferrerInfos<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, ReferrerInfo<ReferralCodeOf<T>>>;
 
-			// Current block number.
-			let current_block = <frame_system::Pallet<T>>::block_number();
+	/// Referrer that every referred player is bound to.
+	#[pallet::storage]
+	pub(super) type Referrers<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
 
-			// Generate new bet.
-			let bet_id = Self::get_and_increment_nonce();
+	/// What the players of every referrer have yielded, per asset.
+	#[pallet::storage]
+	pub(super) type ReferralStatistics<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AssetId,
+		ReferralStats<T::Balance>,
+		ValueQuery,
+	>;
 
-			let bet_data = BetData {
-				id: bet_id,
-				owner: sender.clone(),
-				amount,
-				block: current_block,
-				bet: bet.clone(),
-			};
+	/// Rewards that every referrer can claim, per asset.
+	#[pallet::storage]
+	pub(super) type ReferralRewards<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AssetId,
+		T::Balance,
+		ValueQuery,
+	>;
 
-			// Ongoing bets, including the potentially added one in this extrinsic.
-			let mut ongoing_bets: Vec<BetData<T::AccountId, T::BlockNumber, T::Balance>> =
-				OngoingBets::<T>::iter_values().collect::<Vec<_>>();
-			ongoing_bets.push(bet_data.clone());
+	/// Total of the referral rewards waiting to be claimed, per asset. They are held by the
+	/// pallet account but are not part of the house bankroll.
+	#[pallet::storage]
+	pub(super) type ReferralTotals<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, T::Balance, ValueQuery>;
 
-			// Amount that the pallet can lose in the worst case scenario.
-			let max_payout = Self::max_payout(ongoing_bets);
+	#[pallet::storage]
+	pub(super) type VoucherNonce<T: Config> = StorageValue<_, u64, ValueQuery>;
 
-			// Pallet account.
-			let account_id = Self::account_id();
+	/// Free bet vouchers that were issued and not used yet. Their stake is in the native asset.
+	#[pallet::storage]
+	pub(super) type Vouchers<T: Config> = StorageMap<_, Blake2_128Concat, u64, VoucherOf<T>>;
 
-			// Verify that the pallet has enough free balance, not including the existential deposit.
-			let pallet_reducible_balance = T::Currency::reducible_balance(&account_id, true);
-			ensure!(
-				pallet_reducible_balance >= max_payout,
-				Error::<T>::NotEnoughBalanceInPalletAccount
-			);
+	/// Ids of the vouchers that expire in every block, removed when the block after it starts.
+	#[pallet::storage]
+	pub(super) type VoucherExpiries<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::BlockNumber,
+		BoundedVec<u64, T::MaxVoucherExpiries>,
+		ValueQuery,
+	>;
 
-			// Lock balance for sender.
-			T::Currency::set_lock(PALLET_ID, &sender, amount, WithdrawReasons::RESERVE);
+	#[pallet::storage]
+	pub(super) type ScheduleNonce<T: Config> = StorageValue<_, u64, ValueQuery>;
 
-			// Lock balance for pallet.
-			T::Currency::set_lock(PALLET_ID, &account_id, max_payout, WithdrawReasons::RESERVE);
+	/// Bets placed at the start of every round on behalf of their owners.
+	#[pallet::storage]
+	pub(super) type ScheduledBets<T: Config> =
+		CountedStorageMap<_, Blake2_128Concat, u64, ScheduledBetOf<T>>;
 
-			// Store the bet.
-			OngoingBets::<T>::insert(bet_id, bet_data);
+	#[pallet::storage]
+	pub(super) type StrategyNonce<T: Config> = StorageValue<_, u64, ValueQuery>;
 
-			// Emit an event showing that the claim was created.
-			Self::deposit_event(Event::BetPlaced {
-				who: sender.clone(),
-				bet_id,
-				amount,
-				bet: bet.clone(),
-			});
+	/// Betting strategies being played.
+	#[pallet::storage]
+	pub(super) type Strategies<T: Config> =
+		CountedStorageMap<_, Blake2_128Concat, u64, StrategyOf<T>>;
 
-			Ok(())
-		}
-	}
+	/// Strategy that placed every bet in play that a strategy placed.
+	#[pallet::storage]
+	pub(super) type StrategyBets<T: Config> = StorageMap<_, Blake2_128Concat, u64, u64>;
 
-	/// Helper functions
-	impl<T: Config> Pallet<T> {
-		/// Gets pallet account id.
-		fn account_id() -> T::AccountId {
-			T::PalletId::get().into_account_truncating()
-		}
+	/// Selector that is called back on the owner of a bet once it is settled.
+	#[pallet::storage]
+	pub(super) type BetCallbacks<T: Config> = StorageMap<_, Blake2_128Concat, u64, [u8; 4]>;
 
-		/// Get a nonce from `BetNonce` and increments amount by 1.
-		fn get_and_increment_nonce() -> u64 {
-			let nonce = BetNonce::<T>::get();
-			BetNonce::<T>::put(nonce.wrapping_add(1));
-			nonce
-		}
+	/// Callbacks of settled bets waiting to be made, by position in the queue.
+	#[pallet::storage]
+	pub(super) type CallbackQueue<T: Config> =
+		StorageMap<_, Twox64Concat, u64, (T::AccountId, [u8; 4], BetSettlement<T::Balance>)>;
+
+	/// Position of the first queued callback and of the next one to be queued.
+	#[pallet::storage]
+	pub(super) type CallbackQueueBounds<T: Config> = StorageValue<_, (u64, u64), ValueQuery>;
+
+	/// Whether the lottery stopped taking bets and tickets. Bets in play are still settled.
+	#[pallet::storage]
+	pub(super) type Paused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Minimum and maximum stake of the bets in every asset that has limits.
+	#[pallet::storage]
+	pub(super) type BetLimits<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, (T::Balance, T::Balance)>;
+
+	/// Payout multiples that override the ones of the game, by kind of bet.
+	#[pallet::storage]
+	pub(super) type PayoutMultiples<T: Config> = StorageMap<_, Blake2_128Concat, KindOf<T>, u32>;
+
+	/// Credited winnings by the block in which they expire.
+	#[pallet::storage]
+	pub(super) type PayoutExpiries<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::BlockNumber,
+		Blake2_128Concat,
+		(T::AccountId, T::AssetId),
+		(),
+	>;
+
+	/// First block whose credited winnings were not all returned to the house yet, when more of
+	/// them expired than `MaxExpiriesPerBlock`.
+	#[pallet::storage]
+	pub(super) type PayoutExpiryBacklog<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// Seed, subject and outcome of every roulette spin, so that anybody can verify it.
+	#[pallet::storage]
+	pub(super) type Spins<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, SpinRecord<T::BlockNumber>>;
+
+	/// Totals of the bets settled so far in every round that is being settled, per asset. They
+	/// are reported once every bet of the round is settled.
+	#[pallet::storage]
+	pub(super) type SettlementTotals<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		u32,
+		Blake2_128Concat,
+		T::AssetId,
+		RoundTotals<T::Balance>,
+		ValueQuery,
+	>;
+
+	/// Progressive jackpot of every asset. It is held by the pallet account but is not part of
+	/// the house bankroll.
+	#[pallet::storage]
+	pub(super) type Jackpot<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, T::Balance, ValueQuery>;
+
+	/// Single-outcome pick last hit by a player in every asset, the round in which it was hit and
+	/// how many consecutive rounds it has been hit.
+	#[pallet::storage]
+	pub(super) type JackpotStreaks<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AssetId,
+		(u32, u32, u32),
+	>;
+
+	/// Index of the lottery draw that is currently selling tickets.
+	#[pallet::storage]
+	pub(super) type DrawIndex<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Block from which the current draw stops selling tickets and is bound to randomness, set
+	/// when its first ticket is bought.
+	#[pallet::storage]
+	pub(super) type DrawEnd<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// Tickets of every draw, by draw index and ticket index.
+	#[pallet::storage]
+	pub(super) type Tickets<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, u32, Blake2_128Concat, u32, TicketOf<T>>;
 
-		/// Selects a random number between 0 and 36.
-		fn random_number() -> u32 {
-			let (random_seed, _) = T::LotteryRandomness::random_seed();
-			let random_number = <u32>::decode(&mut random_seed.as_ref())
-				.expect("secure hashes should always be bigger than u32; qed");
-			random_number % 36
+	/// Amount of tickets bought for every draw.
+	#[pallet::storage]
+	pub(super) type TicketCount<T: Config> = StorageMap<_, Blake2_128Concat, u32, u32, ValueQuery>;
+
+	/// Prize pot of the current draw, including rollovers from previous draws.
+	#[pallet::storage]
+	pub(super) type Pot<T: Config> = StorageValue<_, T::Balance, ValueQuery>;
+
+	/// Results of past draws.
+	#[pallet::storage]
+	pub(super) type DrawResults<T: Config> = StorageMap<_, Blake2_128Concat, u32, DrawResultOf<T>>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Whether the lottery starts paused.
+		pub paused: bool,
+		/// Smallest and largest stake of a bet, by asset.
+		pub bet_limits: Vec<(T::AssetId, T::Balance, T::Balance)>,
+		/// Where the randomness of the games comes from.
+		pub randomness_source: Option<RandomnessSourceOf<T>>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { paused: false, bet_limits: Vec::new(), randomness_source: None }
 		}
+	}
 
-		/// States if a `Color` bet wins, given a winner number.
-		fn is_color_winner(color: RouletteColor, winner_number: u32) -> bool {
-			match winner_number.to_color() {
-				Some(winner_color) => winner_color == color,
-				None => false,
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			Paused::<T>::put(self.paused);
+			for (asset, min, max) in &self.bet_limits {
+				assert!(min <= max, "The smallest stake of a bet cannot exceed the largest");
+				BetLimits::<T>::insert(asset, (*min, *max));
+			}
+			if let Some(source) = &self.randomness_source {
+				assert!(Pallet::<T>::is_valid_source(source), "A randomness beacon needs a period");
+				SourceOfRandomness::<T>::put(source);
 			}
 		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut weight = T::DbWeight::get().reads(2);
 
-		/// States if a `Dozen` bet wins, given a winner number.
-		fn is_dozen_winner(dozen: DozenOrColumn, winner_number: u32) -> bool {
-			match winner_number.to_dozen() {
-				Some(winner_dozen) => winner_dozen == dozen,
-				None => false,
+			// Stop taking bets for the current round once its betting window is over.
+			let round = RoundIndex::<T>::get();
+			if let Some(ends_at) = RoundEnds::<T>::get(round) {
+				if now > ends_at {
+					RoundIndex::<T>::put(round.wrapping_add(1));
+					Self::deposit_event(Event::RoundClosed { round, ends_at });
+					// Rounds with commitments are bound once their reveals are over.
+					if !Self::reveals_open(round, ends_at, now) {
+						Self::bind_randomness(&Self::spin_subject(round), now);
+					}
+					weight = weight.saturating_add(T::DbWeight::get().reads_writes(2, 3));
+					weight = weight.saturating_add(Self::place_scheduled_bets());
+				}
 			}
-		}
 
-		/// States if a `Column` bet wins, given a winner number.
-		fn is_column_winner(column: DozenOrColumn, winner_number: u32) -> bool {
-			match winner_number.to_column() {
-				Some(winner_column) => winner_column == column,
-				None => false,
+			weight = weight.saturating_add(Self::play_rounds(now));
+			// Owners are called back once their rounds are fully settled, so that they can place
+			// their next bets.
+			weight = weight.saturating_add(Self::make_callbacks());
+			weight = weight.saturating_add(Self::expire_payouts(now));
+			weight = weight.saturating_add(Self::expire_vouchers(now));
+
+			// Run the lottery draw once its period is over and the randomness it is bound to is
+			// known.
+			if let Some(draw_end) = DrawEnd::<T>::get() {
+				if now >= draw_end {
+					let subject = Self::draw_subject(DrawIndex::<T>::get());
+					if !Self::is_bound(&subject) {
+						Self::bind_randomness(&subject, now);
+					}
+					// Tickets are sold until the block before `draw_end`, so the draw is only run
+					// with randomness known since then.
+					let (randomness, known_since) = T::LotteryRandomness::random(&subject);
+					if known_since >= draw_end {
+						if let Ok(seed) = H256::decode(&mut randomness.as_ref()) {
+							weight = weight.saturating_add(Self::run_draw(now, seed));
+							Self::release_randomness(&subject);
+						}
+					}
+					weight = weight.saturating_add(T::DbWeight::get().reads_writes(5, 2));
+				}
 			}
-		}
 
-		/// States if a `Full` bet wins, given a winner number.
-		fn is_full_winner(number: u32, winner_number: u32) -> bool {
-			winner_number == number
+			weight.saturating_add(T::DbWeight::get().reads(1))
 		}
 
-		/// States if a `Half` bet wins, given a winner number.
-		fn is_half_winner(half: Half, winner_number: u32) -> bool {
-			match winner_number.to_half() {
-				Some(winner_half) => winner_half == half,
-				None => false,
+		fn offchain_worker(_now: T::BlockNumber) {
+			if let Err(error) = Self::submit_awaited_beacons() {
+				log::warn!("Randomness beacon: {}", error);
 			}
 		}
 
-		/// States if an `OddOrEven` bet wins, given a winner number.
-		fn is_odd_or_even_winner(odd_or_even: OddOrEven, winner_number: u32) -> bool {
-			match winner_number.is_even() {
-				true => odd_or_even == OddOrEven::Even,
-				false => odd_or_even == OddOrEven::Odd,
-			}
+		fn integrity_test() {
+			assert!(
+				T::NumbersPerTicket::get() <= T::MaxTicketNumber::get(),
+				"Tickets cannot have more numbers than the ones available"
+			);
+			let total_share = T::PrizeTiers::get()
+				.iter()
+				.fold(0_u64, |acc, share| acc + u64::from(share.deconstruct()));
+			assert!(
+				total_share <= u64::from(Perbill::one().deconstruct()),
+				"Prize tiers cannot share more than the whole pot"
+			);
+			assert!(
+				!T::BettingPeriod::get().is_zero(),
+				"Rounds must take bets for at least a block"
+			);
 		}
+	}
 
-		/// States if a bet wins, given a winner number.
-		fn is_winner(pick: Bet, winner_number: u32) -> bool {
-			match pick {
-				Bet::Color(color) => Self::is_color_winner(color, winner_number),
-				Bet::Full(number) => Self::is_full_winner(number, winner_number),
-				Bet::Dozen(dozen) => Self::is_dozen_winner(dozen, winner_number),
-				Bet::Column(column) => Self::is_column_winner(column, winner_number),
-				Bet::Half(half) => Self::is_half_winner(half, winner_number),
-				Bet::OddOrEven(odd_or_even) => {
-					Self::is_odd_or_even_winner(odd_or_even, winner_number)
-				},
-			}
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(0)]
+		pub fn place_bet(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			amount: T::Balance,
+			bet: PickOf<T>,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			// This function will return an error if the extrinsic is not signed.
+			let sender = ensure_signed(origin)?;
+
+			Self::do_place_bet(sender, asset, amount, bet, None)?;
+
+			Ok(())
+		}
+
+		/// Places a bet staked by a free bet voucher instead of the balance of the sender.
+		#[pallet::weight(0)]
+		pub fn place_bet_with_voucher(
+			origin: OriginFor<T>,
+			voucher_id: u64,
+			bet: PickOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let voucher = Vouchers::<T>::get(voucher_id).ok_or(Error::<T>::UnknownVoucher)?;
+			ensure!(voucher.owner == sender, Error::<T>::NotVoucherOwner);
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now <= voucher.expires_at, Error::<T>::VoucherExpired);
+			ensure!(
+				voucher.bet_kinds.is_empty() || voucher.bet_kinds.contains(&T::Game::kind(&bet)),
+				Error::<T>::BetKindNotAllowed
+			);
+
+			let bet_id = Self::do_place_bet(
+				sender,
+				T::NativeAssetId::get(),
+				voucher.amount,
+				bet,
+				Some(voucher_id),
+			)?;
+			Vouchers::<T>::remove(voucher_id);
+
+			Self::deposit_event(Event::VoucherRedeemed { voucher_id, bet_id });
+
+			Ok(())
+		}
+
+		#[pallet::weight(0)]
+		pub fn buy_ticket(origin: OriginFor<T>, numbers: TicketNumbersOf<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(!Paused::<T>::get(), Error::<T>::Paused);
+			Self::ensure_valid_ticket(&numbers)?;
+
+			// Tickets are not sold once the draw is bound to its randomness, which is only
+			// known `RandomnessDelay` blocks later at the earliest.
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			if let Some(draw_end) = DrawEnd::<T>::get() {
+				ensure!(current_block < draw_end, Error::<T>::DrawClosed);
+			}
+
+			let draw = DrawIndex::<T>::get();
+			let ticket = TicketCount::<T>::get(draw);
+			ensure!(ticket < T::MaxTicketsPerDraw::get(), Error::<T>::TooManyTickets);
+
+			// Tickets are paid in the native currency.
+			let price = T::TicketPrice::get();
+			let native = T::NativeAssetId::get();
+			let reducible_balance = Self::reducible_balance(native, &sender);
+			ensure!(reducible_balance >= price, Error::<T>::NotEnoughBalance);
+
+			// The price goes straight into the pot.
+			Self::transfer(native, &sender, &Self::draw_account_id(), price)?;
+			Pot::<T>::mutate(|pot| *pot = pot.saturating_add(price));
+
+			// The first ticket of a draw schedules it.
+			if DrawEnd::<T>::get().is_none() {
+				DrawEnd::<T>::put(current_block.saturating_add(T::DrawPeriod::get()));
+			}
+
+			Tickets::<T>::insert(
+				draw,
+				ticket,
+				Ticket { owner: sender.clone(), numbers: numbers.clone() },
+			);
+			TicketCount::<T>::insert(draw, ticket + 1);
+
+			Self::deposit_event(Event::TicketBought { draw, ticket, who: sender, numbers });
+
+			Ok(())
+		}
+
+		/// Commits to the `blake2_256` hash of some entropy for the round taking bets, holding
+		/// `CommitmentDeposit` until it is revealed.
+		#[pallet::weight(0)]
+		pub fn commit_entropy(origin: OriginFor<T>, commitment: H256) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let round = RoundIndex::<T>::get();
+			ensure!(RoundEnds::<T>::contains_key(round), Error::<T>::RoundNotOpen);
+			ensure!(Self::can_commit(round, &sender), Error::<T>::NotAllowedToCommit);
+			ensure!(
+				!EntropyCommitments::<T>::contains_key(round, &sender),
+				Error::<T>::AlreadyCommitted
+			);
+			ensure!(
+				EntropyCommitments::<T>::iter_key_prefix(round).count() <
+					T::MaxCommitments::get() as usize,
+				Error::<T>::TooManyCommitments
+			);
+
+			// The deposit is held by the pallet account.
+			let deposit = T::CommitmentDeposit::get();
+			let native = T::NativeAssetId::get();
+			let reducible_balance = Self::reducible_balance(native, &sender);
+			ensure!(reducible_balance >= deposit, Error::<T>::NotEnoughBalance);
+			Self::transfer(native, &sender, &Self::account_id(), deposit)?;
+			CommitmentDeposits::<T>::mutate(|deposits| {
+				*deposits = deposits.saturating_add(deposit)
+			});
+
+			EntropyCommitments::<T>::insert(
+				round,
+				&sender,
+				EntropyCommitment { commitment, deposit, reveal: None },
+			);
+
+			Self::deposit_event(Event::EntropyCommitted { round, who: sender, commitment });
+
+			Ok(())
+		}
+
+		/// Reveals the entropy committed to a closed round that is not bound to its randomness yet,
+		/// getting the deposit back.
+		#[pallet::weight(0)]
+		pub fn reveal_entropy(origin: OriginFor<T>, round: u32, entropy: H256) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(
+				round < RoundIndex::<T>::get() &&
+					RoundEnds::<T>::contains_key(round) &&
+					!Self::is_bound(&Self::spin_subject(round)),
+				Error::<T>::RevealNotOpen
+			);
+			let mut commitment =
+				EntropyCommitments::<T>::get(round, &sender).ok_or(Error::<T>::NoCommitment)?;
+			ensure!(commitment.reveal.is_none(), Error::<T>::AlreadyRevealed);
+			ensure!(
+				H256(blake2_256(entropy.as_ref())) == commitment.commitment,
+				Error::<T>::InvalidReveal
+			);
+
+			Self::transfer(
+				T::NativeAssetId::get(),
+				&Self::account_id(),
+				&sender,
+				commitment.deposit,
+			)?;
+			CommitmentDeposits::<T>::mutate(|deposits| {
+				*deposits = deposits.saturating_sub(commitment.deposit)
+			});
+
+			commitment.reveal = Some(entropy);
+			EntropyCommitments::<T>::insert(round, &sender, commitment);
+
+			Self::deposit_event(Event::EntropyRevealed { round, who: sender, entropy });
+
+			Ok(())
+		}
+
+		/// Allows or disallows an account to commit entropy to rounds it does not bet on.
+		#[pallet::weight(0)]
+		pub fn set_revealer(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			allowed: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			if allowed {
+				Revealers::<T>::insert(&who, ());
+			} else {
+				Revealers::<T>::remove(&who);
+			}
+
+			Self::deposit_event(Event::RevealerSet { who, allowed });
+
+			Ok(())
+		}
+
+		/// Submits a round of the randomness beacon that games are bound to, signed by the key
+		/// of the beacon. It is sent as an unsigned transaction by the offchain worker.
+		#[pallet::weight(0)]
+		pub fn submit_beacon(
+			origin: OriginFor<T>,
+			round: u64,
+			randomness: H256,
+			signature: ed25519::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+
+			let awaited = Self::awaited_beacon(round).ok_or(Error::<T>::UnexpectedBeaconRound)?;
+			ensure!(
+				Self::verify_beacon(awaited.public_key, round, &randomness, &signature),
+				Error::<T>::InvalidBeaconSignature
+			);
+
+			AwaitedBeaconRounds::<T>::insert(
+				round,
+				AwaitedBeacon {
+					randomness: Some((randomness, <frame_system::Pallet<T>>::block_number())),
+					..awaited
+				},
+			);
+
+			Self::deposit_event(Event::BeaconRoundSubmitted { round, randomness });
+
+			Ok(())
+		}
+
+		/// Claims the winnings credited to the caller in `asset`.
+		#[pallet::weight(0)]
+		pub fn claim_winnings(origin: OriginFor<T>, asset: T::AssetId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			Self::pay_pending(&sender, asset).map(|_| ())
+		}
+
+		/// Pays the winnings credited to `who` in `asset`, on its behalf.
+		#[pallet::weight(0)]
+		pub fn claim_for(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			asset: T::AssetId,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			Self::pay_pending(&who, asset).map(|_| ())
+		}
+
+		/// Registers the caller as a referrer that players can bind to with `code`.
+		#[pallet::weight(0)]
+		pub fn register_referrer(origin: OriginFor<T>, code: ReferralCodeOf<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(!ReferrerInfos::<T>::contains_key(&sender), Error::<T>::AlreadyReferrer);
+			ensure!(!ReferralCodes::<T>::contains_key(&code), Error::<T>::ReferralCodeTaken);
+
+			ReferralCodes::<T>::insert(&code, &sender);
+			ReferrerInfos::<T>::insert(&sender, ReferrerInfo { code: code.clone(), players: 0 });
+
+			Self::deposit_event(Event::ReferrerRegistered { who: sender, code });
+
+			Ok(())
+		}
+
+		/// Binds the caller to the referrer of `code`. Players can only be bound once.
+		#[pallet::weight(0)]
+		pub fn bind_referrer(origin: OriginFor<T>, code: ReferralCodeOf<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(!Referrers::<T>::contains_key(&sender), Error::<T>::AlreadyReferred);
+			let referrer = ReferralCodes::<T>::get(&code).ok_or(Error::<T>::UnknownReferralCode)?;
+			ensure!(referrer != sender, Error::<T>::SelfReferral);
+
+			Referrers::<T>::insert(&sender, &referrer);
+			ReferrerInfos::<T>::mutate(&referrer, |info| {
+				if let Some(info) = info {
+					info.players = info.players.saturating_add(1);
+				}
+			});
+
+			Self::deposit_event(Event::ReferrerBound { who: sender, referrer });
+
+			Ok(())
+		}
+
+		/// Claims the referral rewards of the caller in `asset`.
+		#[pallet::weight(0)]
+		pub fn claim_referral_rewards(origin: OriginFor<T>, asset: T::AssetId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let amount = ReferralRewards::<T>::get(&sender, asset);
+			ensure!(!amount.is_zero(), Error::<T>::NothingToClaim);
+
+			Self::transfer(asset, &Self::account_id(), &sender, amount)?;
+			ReferralRewards::<T>::remove(&sender, asset);
+			ReferralTotals::<T>::mutate(asset, |total| *total = total.saturating_sub(amount));
+
+			Self::deposit_event(Event::ReferralRewardsClaimed { who: sender, asset, amount });
+
+			Ok(())
+		}
+
+		/// Schedules `bet` to be placed with `amount` in the current round and at the start of
+		/// the following ones, until `rounds` bets are placed or `budget` cannot cover another.
+		#[pallet::weight(0)]
+		pub fn schedule_bet(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			bet: PickOf<T>,
+			amount: T::Balance,
+			rounds: u32,
+			budget: T::Balance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(rounds > 0 && budget >= amount, Error::<T>::InvalidSchedule);
+			ensure!(
+				ScheduledBets::<T>::count() < T::MaxScheduledBets::get(),
+				Error::<T>::TooManyScheduledBets
+			);
+
+			let schedule_id = ScheduleNonce::<T>::mutate(|nonce| {
+				let schedule_id = *nonce;
+				*nonce = nonce.saturating_add(1);
+				schedule_id
+			});
+			Self::deposit_event(Event::BetScheduled {
+				schedule_id,
+				who: sender.clone(),
+				asset,
+				amount,
+				bet: bet.clone(),
+				rounds,
+				budget,
+			});
+
+			// The first bet goes to the current round, with the same checks as `place_bet`.
+			Self::do_place_bet(sender.clone(), asset, amount, bet.clone(), None)?;
+			let schedule = ScheduledBet {
+				owner: sender,
+				asset,
+				amount,
+				bet,
+				rounds: rounds - 1,
+				budget: budget.saturating_sub(amount),
+			};
+			Self::store_schedule(schedule_id, schedule);
+
+			Ok(())
+		}
+
+		/// Stops placing a scheduled bet. Bets already placed are still played.
+		#[pallet::weight(0)]
+		pub fn cancel_scheduled_bet(origin: OriginFor<T>, schedule_id: u64) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let schedule =
+				ScheduledBets::<T>::get(schedule_id).ok_or(Error::<T>::UnknownSchedule)?;
+			ensure!(schedule.owner == sender, Error::<T>::NotScheduleOwner);
+
+			ScheduledBets::<T>::remove(schedule_id);
+			Self::deposit_event(Event::ScheduleEnded { schedule_id });
+
+			Ok(())
+		}
+
+		/// Starts a betting strategy that bets on `bet` in every round, staking `base_stake`
+		/// times the units of `progression`. It stops before its next stake could take its
+		/// losses over `stop_loss`, or once its profit reaches `take_profit`.
+		#[pallet::weight(0)]
+		pub fn register_strategy(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			bet: PickOf<T>,
+			base_stake: T::Balance,
+			progression: Progression,
+			stop_loss: T::Balance,
+			take_profit: T::Balance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(
+				!base_stake.is_zero() && base_stake <= stop_loss && !take_profit.is_zero(),
+				Error::<T>::InvalidStrategy
+			);
+			ensure!(
+				Strategies::<T>::count() < T::MaxStrategies::get(),
+				Error::<T>::TooManyStrategies
+			);
+
+			let strategy_id = StrategyNonce::<T>::mutate(|nonce| {
+				let strategy_id = *nonce;
+				*nonce = nonce.saturating_add(1);
+				strategy_id
+			});
+			Self::deposit_event(Event::StrategyRegistered {
+				strategy_id,
+				who: sender.clone(),
+				asset,
+				bet: bet.clone(),
+				base_stake,
+				progression: progression.clone(),
+			});
+
+			// The first bet goes to the current round, with the same checks as `place_bet`.
+			let bet_id = Self::do_place_bet(sender.clone(), asset, base_stake, bet.clone(), None)?;
+			StrategyBets::<T>::insert(bet_id, strategy_id);
+			Self::deposit_event(Event::StrategyStep {
+				strategy_id,
+				bet_id,
+				step: 0,
+				stake: base_stake,
+			});
+			let strategy = Strategy {
+				owner: sender,
+				asset,
+				bet,
+				base_stake,
+				progression,
+				stop_loss,
+				take_profit,
+				step: 0,
+				staked: T::Balance::zero(),
+				paid: T::Balance::zero(),
+			};
+			Strategies::<T>::insert(strategy_id, strategy);
+
+			Ok(())
+		}
+
+		/// Stops a betting strategy. The bet in play is still played.
+		#[pallet::weight(0)]
+		pub fn cancel_strategy(origin: OriginFor<T>, strategy_id: u64) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let strategy = Strategies::<T>::get(strategy_id).ok_or(Error::<T>::UnknownStrategy)?;
+			ensure!(strategy.owner == sender, Error::<T>::NotStrategyOwner);
+
+			Self::stop_strategy(strategy_id, strategy, StopReason::Cancelled);
+
+			Ok(())
+		}
+
+		/// Issues a free bet voucher of `amount` in the native asset to `who`, usable until
+		/// `expires_at` for the given kinds of bets, or for any of them if none is given. Unused
+		/// vouchers are removed once they expire.
+		#[pallet::weight(0)]
+		pub fn issue_voucher(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			amount: T::Balance,
+			expires_at: T::BlockNumber,
+			bet_kinds: VoucherBetKindsOf<T>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(!amount.is_zero() && expires_at > now, Error::<T>::InvalidVoucher);
+
+			let voucher_id = VoucherNonce::<T>::get();
+			VoucherExpiries::<T>::try_mutate(expires_at, |expiring| expiring.try_push(voucher_id))
+				.map_err(|_| Error::<T>::TooManyVoucherExpiries)?;
+			VoucherNonce::<T>::put(voucher_id.saturating_add(1));
+			Vouchers::<T>::insert(
+				voucher_id,
+				Voucher { owner: who.clone(), amount, expires_at, bet_kinds },
+			);
+
+			Self::deposit_event(Event::VoucherIssued { voucher_id, who, amount, expires_at });
+
+			Ok(())
+		}
+
+		/// Stops or resumes taking bets and tickets. Bets in play are still settled and winnings
+		/// can still be claimed.
+		#[pallet::weight(0)]
+		pub fn set_paused(origin: OriginFor<T>, paused: bool) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			Paused::<T>::put(paused);
+
+			Self::deposit_event(Event::PausedSet { paused });
+
+			Ok(())
+		}
+
+		/// Limits the stakes of the bets in `asset` between a minimum and a maximum, or removes
+		/// the limits if none are given.
+		#[pallet::weight(0)]
+		pub fn set_bet_limits(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			limits: Option<(T::Balance, T::Balance)>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			match limits {
+				Some((min, max)) => {
+					ensure!(min <= max, Error::<T>::InvalidBetLimits);
+					BetLimits::<T>::insert(asset, (min, max));
+				},
+				None => BetLimits::<T>::remove(asset),
+			}
+
+			Self::deposit_event(Event::BetLimitsSet { asset, limits });
+
+			Ok(())
+		}
+
+		/// Overrides the payout multiple of a kind of bet, or goes back to the one of the game
+		/// if none is given. Only new bets take the new multiple: bets in play keep the one they
+		/// were placed with, and so does the exposure recorded for them.
+		#[pallet::weight(0)]
+		pub fn set_payout_multiple(
+			origin: OriginFor<T>,
+			kind: KindOf<T>,
+			multiple: Option<u32>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			match multiple {
+				Some(multiple) => {
+					ensure!(multiple >= 1, Error::<T>::InvalidPayoutMultiple);
+					PayoutMultiples::<T>::insert(&kind, multiple);
+				},
+				None => PayoutMultiples::<T>::remove(&kind),
+			}
+
+			Self::deposit_event(Event::PayoutMultipleSet { kind, multiple });
+
+			Ok(())
+		}
+
+		/// Withdraws `amount` of the house balance in `asset` to `dest`. Only the balance that
+		/// does not back bets in play can be withdrawn.
+		#[pallet::weight(0)]
+		pub fn withdraw_house(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			amount: T::Balance,
+			dest: T::AccountId,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			let free = Self::house_balance(asset).saturating_sub(Self::total_exposure(asset));
+			ensure!(amount <= free, Error::<T>::NotEnoughFreeHouseBalance);
+
+			Self::transfer(asset, &Self::account_id(), &dest, amount)?;
+
+			Self::deposit_event(Event::HouseWithdrawn { asset, amount, dest });
+
+			Ok(())
+		}
+
+		/// Sets where the randomness of the games comes from. Games that already stopped taking
+		/// bets keep the randomness they are bound to.
+		#[pallet::weight(0)]
+		pub fn set_randomness_source(
+			origin: OriginFor<T>,
+			source: RandomnessSourceOf<T>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(Self::is_valid_source(&source), Error::<T>::InvalidRandomnessSource);
+			SourceOfRandomness::<T>::put(&source);
+
+			Self::deposit_event(Event::RandomnessSourceSet { source });
+
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::submit_beacon { round, randomness, signature } = call {
+				let awaited = match Self::awaited_beacon(*round) {
+					Some(awaited) => awaited,
+					None => return InvalidTransaction::Stale.into(),
+				};
+				if !Self::verify_beacon(awaited.public_key, *round, randomness, signature) {
+					return InvalidTransaction::BadProof.into()
+				}
+
+				ValidTransaction::with_tag_prefix("LotteryBeacon")
+					.priority(T::BeaconUnsignedPriority::get())
+					.and_provides(round)
+					.longevity(5)
+					.propagate(true)
+					.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+	}
+
+	/// Helper functions
+	impl<T: Config> Pallet<T> {
+		/// Gets pallet account id.
+		pub(crate) fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// Balance of `who` in `asset` that can be spent without killing the account.
+		pub(crate) fn reducible_balance(asset: T::AssetId, who: &T::AccountId) -> T::Balance {
+			if asset == T::NativeAssetId::get() {
+				<T::Currency as fungible::Inspect<_>>::reducible_balance(who, true)
+			} else {
+				<T::Assets as fungibles::Inspect<_>>::reducible_balance(asset, who, true)
+			}
+		}
+
+		/// Transfers `amount` of `asset` from `source` to `dest`, keeping `source` alive.
+		pub(crate) fn transfer(
+			asset: T::AssetId,
+			source: &T::AccountId,
+			dest: &T::AccountId,
+			amount: T::Balance,
+		) -> Result<T::Balance, DispatchError> {
+			if asset == T::NativeAssetId::get() {
+				<T::Currency as fungible::Transfer<_>>::transfer(source, dest, amount, true)
+			} else {
+				<T::Assets as fungibles::Transfer<_>>::transfer(asset, source, dest, amount, true)
+			}
+		}
+
+		/// Plays, in order, the closed rounds whose randomness is already known, up to
+		/// `MaxRoundsPerBlock` of them and `MaxBetsPerBlock` of their bets.
+		fn play_rounds(now: T::BlockNumber) -> Weight {
+			let mut weight = T::DbWeight::get().reads(2);
+
+			let current_round = RoundIndex::<T>::get();
+			let mut round = NextRoundToPlay::<T>::get();
+			let last_round = current_round.min(round.saturating_add(T::MaxRoundsPerBlock::get()));
+			let mut bets_left = T::MaxBetsPerBlock::get();
+			while round < last_round && bets_left > 0 {
+				// Every closed round has its end recorded.
+				let ends_at = match RoundEnds::<T>::get(round) {
+					Some(ends_at) => ends_at,
+					None => break,
+				};
+
+				// A round that was partly settled keeps the spin it was played with.
+				weight = weight.saturating_add(T::DbWeight::get().reads(2));
+				let spin = match Spins::<T>::get(round) {
+					Some(spin) => spin,
+					None => {
+						// Players that committed to entropy get until the reveal deadline to
+						// reveal it, and rounds closed without a source of randomness are bound
+						// once one is set.
+						let subject = Self::spin_subject(round);
+						if !Self::is_bound(&subject) &&
+							(Self::reveals_open(round, ends_at, now) ||
+								Self::bind_randomness(&subject, now).is_none())
+						{
+							break
+						}
+
+						// The seed must not have been known while the round was taking bets.
+						let (randomness, known_since) = T::LotteryRandomness::random(&subject);
+						if known_since <= ends_at {
+							break
+						}
+						let seed = match H256::decode(&mut randomness.as_ref()) {
+							Ok(seed) => seed,
+							Err(_) => break,
+						};
+
+						let round_seed = Self::round_seed(round, seed);
+						Self::release_randomness(&subject);
+						weight = weight.saturating_add(T::DbWeight::get().reads_writes(3, 3));
+						Self::spin(round, round_seed, now)
+					},
+				};
+
+				// A round with more bets than are left to settle in this block is settled
+				// further in the next ones.
+				let (settled, settle_weight) = Self::play_roulette(round, &spin, now, bets_left);
+				weight = weight.saturating_add(settle_weight);
+				if settled == bets_left {
+					break
+				}
+				bets_left -= settled;
+
+				RoundEnds::<T>::remove(round);
+				weight = weight.saturating_add(T::DbWeight::get().writes(1));
+				round = round.wrapping_add(1);
+			}
+			NextRoundToPlay::<T>::put(round);
+
+			weight.saturating_add(T::DbWeight::get().writes(1))
+		}
+
+		/// Settles up to `limit` bets of a round with its spin, returning how many were settled.
+		/// Once fewer than `limit` were left, every bet of the round is settled, so its totals are
+		/// reported and nothing is at stake in it anymore.
+		fn play_roulette(
+			round: u32,
+			spin: &SpinRecord<T::BlockNumber>,
+			now: T::BlockNumber,
+			limit: u32,
+		) -> (u32, Weight) {
+			let db_weight = T::DbWeight::get();
+			let mut weight = Weight::zero();
+			// Pallet account.
+			let account_id = Self::account_id();
+			let winner_number = spin.outcome;
+
+			// Pay out winners, keeping track of totals for every asset. Stakes of losing bets
+			// are already held by the pallet account.
+			let mut totals: Vec<SpinTotals<T::AssetId, T::Balance>> = Vec::new();
+			// Players whose single-outcome streak is long enough to win the jackpot.
+			let mut jackpot_winners: Vec<(T::AssetId, T::AccountId)> = Vec::new();
+			// What the house made from referred players.
+			let mut referred_results = ReferredResultsOf::<T>::new();
+			// Stakes and payouts of the bets placed by strategies.
+			let mut strategy_results: Vec<(u64, T::Balance, T::Balance)> = Vec::new();
+			let mut settled = 0_u32;
+			for (bet_id, bet_data) in OngoingBets::<T>::drain_prefix(round).take(limit as usize) {
+				settled += 1;
+				OngoingBetsByOwner::<T>::remove(&bet_data.owner, bet_id);
+				// Every bet is taken out of play, looked up for a strategy, a callback and a
+				// referrer, recorded on chain and in the offchain index, and announced.
+				weight = weight.saturating_add(db_weight.reads_writes(5, 11));
+
+				let position = match totals.iter().position(|totals| totals.asset == bet_data.asset)
+				{
+					Some(position) => position,
+					None => {
+						totals.push(SpinTotals {
+							asset: bet_data.asset,
+							players: 0_u32,
+							staked: T::Balance::default(),
+							income: T::Balance::default(),
+							payout: T::Balance::default(),
+						});
+						totals.len() - 1
+					},
+				};
+				let asset_totals = &mut totals[position];
+				asset_totals.players += 1;
+				// The stake of a bet placed with a voucher was never paid.
+				let staked = match bet_data.voucher {
+					Some(_) => T::Balance::zero(),
+					None => bet_data.amount,
+				};
+				asset_totals.staked = asset_totals.staked.saturating_add(staked);
+
+				let is_winner = T::Game::is_winner(&bet_data.bet, winner_number);
+
+				if is_winner {
+					// Transfer prize to account.
+					let payout_amount = Self::bet_payout(&bet_data);
+
+					asset_totals.payout = asset_totals.payout.saturating_add(payout_amount);
+					Self::record_referred_bet(
+						&mut referred_results,
+						&bet_data.owner,
+						bet_data.asset,
+						staked,
+						payout_amount,
+					);
+
+					// Winnings are pushed right away unless players pull them. Failed pushes are
+					// credited as well, so they can still be claimed.
+					let pushed = match T::PullPayouts::get() {
+						true => None,
+						false => Some(Self::transfer(
+							bet_data.asset,
+							&account_id,
+							&bet_data.owner,
+							payout_amount,
+						)),
+					};
+					let pending = !matches!(pushed, Some(Ok(_)));
+					if pushed.is_some() {
+						weight = weight.saturating_add(db_weight.reads_writes(2, 2));
+					}
+					if pending {
+						Self::credit_payout(&bet_data.owner, bet_data.asset, payout_amount, now);
+						weight = weight.saturating_add(db_weight.reads_writes(2, 4));
+					}
+					if let Some(Err(error)) = pushed {
+						log::debug!(
+							target: "runtime::lottery",
+							"Payout of bet {} credited instead: {:?}",
+							bet_id,
+							error,
+						);
+					}
+
+					Self::deposit_event_for(
+						&bet_data.owner,
+						Event::BetSettled {
+							bet_id,
+							who: bet_data.owner.clone(),
+							won: true,
+							payout: payout_amount,
+							pending,
+						},
+					);
+
+					if let Some(number) = T::Game::single_outcome(&bet_data.bet) {
+						weight = weight.saturating_add(db_weight.reads_writes(1, 1));
+						if Self::extend_jackpot_streak(
+							&bet_data.owner,
+							bet_data.asset,
+							number,
+							round,
+						) {
+							jackpot_winners.push((bet_data.asset, bet_data.owner.clone()));
+						}
+					}
+				} else {
+					asset_totals.income = asset_totals.income.saturating_add(staked);
+					Self::record_referred_bet(
+						&mut referred_results,
+						&bet_data.owner,
+						bet_data.asset,
+						staked,
+						T::Balance::zero(),
+					);
+					Self::deposit_event_for(
+						&bet_data.owner,
+						Event::BetSettled {
+							bet_id,
+							who: bet_data.owner.clone(),
+							won: false,
+							payout: T::Balance::zero(),
+							pending: false,
+						},
+					);
+				}
+
+				// Strategies and callbacks follow up on the bet once the round is settled.
+				let paid = match is_winner {
+					true => Self::bet_payout(&bet_data),
+					false => T::Balance::zero(),
+				};
+				if let Some(strategy_id) = StrategyBets::<T>::take(bet_id) {
+					strategy_results.push((strategy_id, bet_data.amount, paid));
+				}
+				if let Some(selector) = BetCallbacks::<T>::take(bet_id) {
+					let settlement = BetSettlement {
+						bet_id,
+						round,
+						outcome: winner_number,
+						won: is_winner,
+						payout: paid,
+					};
+					Self::queue_callback(bet_data.owner.clone(), selector, settlement);
+					weight = weight.saturating_add(db_weight.reads_writes(1, 2));
+				}
+
+				Self::record_settled_bet(BetRecord {
+					bet: bet_data,
+					round,
+					outcome: winner_number,
+					won: is_winner,
+					payout: paid,
+					settled_at: now,
+				});
+			}
+
+			let settled_all = settled < limit;
+			if settled_all {
+				RoundBetCounts::<T>::remove(round);
+			} else {
+				RoundBetCounts::<T>::mutate(round, |count| *count = count.saturating_sub(settled));
+			}
+			weight = weight.saturating_add(db_weight.reads_writes(1, 1));
+
+			for asset_totals in totals {
+				SettlementTotals::<T>::mutate(round, asset_totals.asset, |round_totals| {
+					round_totals.players =
+						round_totals.players.saturating_add(asset_totals.players);
+					round_totals.income = round_totals.income.saturating_add(asset_totals.income);
+					round_totals.payout = round_totals.payout.saturating_add(asset_totals.payout);
+				});
+				weight = weight.saturating_add(db_weight.reads_writes(1, 1));
+
+				// Part of the stakes feeds the progressive jackpot.
+				let contribution = T::JackpotContribution::get() * asset_totals.staked;
+				if !contribution.is_zero() {
+					let jackpot = Jackpot::<T>::mutate(asset_totals.asset, |jackpot| {
+						*jackpot = jackpot.saturating_add(contribution);
+						*jackpot
+					});
+					Self::deposit_event(Event::JackpotIncreased {
+						asset: asset_totals.asset,
+						contribution,
+						jackpot,
+					});
+					weight = weight.saturating_add(db_weight.reads_writes(1, 2));
+				}
+			}
+
+			if settled_all {
+				for (asset, round_totals) in SettlementTotals::<T>::drain_prefix(round) {
+					// Nothing is at stake anymore for this asset.
+					Exposure::<T>::remove(asset, round);
+
+					Self::deposit_event(Event::RoulettePlayed {
+						round,
+						asset,
+						seed: spin.seed,
+						winner_number,
+						players: round_totals.players,
+						income: round_totals.income,
+						payout: round_totals.payout,
+					});
+					weight = weight.saturating_add(db_weight.reads_writes(1, 3));
+				}
+			}
+
+			for (asset, who) in jackpot_winners {
+				Self::pay_jackpot(asset, who, winner_number);
+				weight = weight.saturating_add(db_weight.reads_writes(3, 4));
+			}
+
+			weight = weight.saturating_add(Self::reward_referrers(round, referred_results));
+			weight = weight.saturating_add(Self::advance_strategies(strategy_results));
+
+			(settled, weight)
+		}
+
+		/// Deposits an event with `who` as an indexed topic.
+		fn deposit_event_for(who: &T::AccountId, event: Event<T>) {
+			let event = <T as Config>::RuntimeEvent::from(event);
+			let topic = T::Hashing::hash_of(who);
+			<frame_system::Pallet<T>>::deposit_event_indexed(&[topic], event.into());
+		}
+
+		/// Records that `who` hit a straight-up `number` in `round` with a bet in `asset`. Returns
+		/// whether the streak is long enough to win the progressive jackpot of `asset`, in which
+		/// case the streak starts over.
+		fn extend_jackpot_streak(
+			who: &T::AccountId,
+			asset: T::AssetId,
+			number: u32,
+			round: u32,
+		) -> bool {
+			let streak = match JackpotStreaks::<T>::get(who, asset) {
+				// The same number was hit in this round with another bet.
+				Some((last_number, last_round, _))
+					if last_number == number && last_round == round =>
+					return false,
+				Some((last_number, last_round, streak))
+					if last_number == number && last_round.wrapping_add(1) == round =>
+					streak + 1,
+				_ => 1,
+			};
+
+			if T::JackpotContribution::get().is_zero() || streak < T::JackpotStreak::get() {
+				JackpotStreaks::<T>::insert(who, asset, (number, round, streak));
+				false
+			} else {
+				JackpotStreaks::<T>::remove(who, asset);
+				true
+			}
+		}
+
+		/// Pays the share of the progressive jackpot of `asset` to `who`.
+		fn pay_jackpot(asset: T::AssetId, who: T::AccountId, number: u32) {
+			let jackpot = Jackpot::<T>::get(asset);
+			let amount = T::JackpotPayout::get() * jackpot;
+			if amount.is_zero() {
+				return
+			}
+
+			if Self::transfer(asset, &Self::account_id(), &who, amount).is_ok() {
+				Jackpot::<T>::insert(asset, jackpot.saturating_sub(amount));
+				Self::deposit_event(Event::JackpotWon { asset, who, number, amount });
+			}
+		}
+
+		/// Balance of the house in `asset` that can back bets, which excludes the progressive
+		/// jackpot, unclaimed winnings and referral rewards, commitment deposits and the
+		/// existential deposit.
+		pub(crate) fn house_balance(asset: T::AssetId) -> T::Balance {
+			let mut balance = Self::reducible_balance(asset, &Self::account_id())
+				.saturating_sub(Jackpot::<T>::get(asset))
+				.saturating_sub(PendingTotals::<T>::get(asset))
+				.saturating_sub(ReferralTotals::<T>::get(asset));
+			if asset == T::NativeAssetId::get() {
+				balance = balance.saturating_sub(CommitmentDeposits::<T>::get());
+			}
+			balance
+		}
+
+		/// Amount that the house can lose in `asset` in the worst case scenario, adding up every
+		/// round that has not been played yet.
+		pub(crate) fn total_exposure(asset: T::AssetId) -> T::Balance {
+			Exposure::<T>::iter_prefix_values(asset)
+				.fold(T::Balance::zero(), |acc, exposure| acc.saturating_add(exposure))
+		}
+
+		/// Get a nonce from `BetNonce` and increments amount by 1.
+		fn get_and_increment_nonce() -> u64 {
+			let nonce = BetNonce::<T>::get();
+			BetNonce::<T>::put(nonce.wrapping_add(1));
+			nonce
+		}
+
+		/// Subject mixed with the random seed to derive the outcome of a round.
+		pub(crate) fn spin_subject(round: u32) -> Vec<u8> {
+			(b"roulette", round).encode()
+		}
+
+		/// Selects a random outcome of the game for a round and records how it was obtained.
+		fn spin(round: u32, seed: H256, now: T::BlockNumber) -> SpinRecord<T::BlockNumber> {
+			let subject = Self::spin_subject(round);
+			let spin = SpinRecord {
+				seed,
+				outcome: traits::verify_outcome::<T::Game>(seed.as_ref(), &subject),
+				subject: BoundedVec::truncate_from(subject),
+				derivation: Derivation::Blake2Uniform,
+				block: now,
+			};
+			Spins::<T>::insert(round, spin.clone());
+			spin
+		}
+
+		/// Places a bet in the round taking bets, staked by `sender` or by a voucher. Returns the
+		/// id of the bet.
+		pub(crate) fn do_place_bet(
+			sender: T::AccountId,
+			asset: T::AssetId,
+			amount: T::Balance,
+			bet: PickOf<T>,
+			voucher: Option<u64>,
+		) -> Result<u64, DispatchError> {
+			ensure!(!Paused::<T>::get(), Error::<T>::Paused);
+
+			// Do not take bets that cannot be played.
+			ensure!(T::Game::validate(&bet), Error::<T>::OutOfRange);
+
+			if let Some((min, max)) = BetLimits::<T>::get(asset) {
+				ensure!(amount >= min, Error::<T>::BetTooSmall);
+				ensure!(amount <= max, Error::<T>::BetTooLarge);
+			}
+
+			// Verify that the buyer has enough balance to afford the bet and is
+			// left with more than the existential deposit. Vouchers stake for the buyer.
+			if voucher.is_none() {
+				let reducible_balance = Self::reducible_balance(asset, &sender);
+				ensure!(reducible_balance >= amount, Error::<T>::NotEnoughBalance);
+			}
+
+			// Current block number.
+			let current_block = <frame_system::Pallet<T>>::block_number();
+
+			// Round that takes the bet.
+			let round = RoundIndex::<T>::get();
+
+			// Generate new bet.
+			let bet_id = Self::get_and_increment_nonce();
+
+			let bet_data = BetData {
+				id: bet_id,
+				owner: sender.clone(),
+				asset,
+				amount,
+				block: current_block,
+				bet: bet.clone(),
+				voucher,
+				multiple: Self::payout_multiple(&bet),
+			};
+
+			// Ongoing bets of the round in the same asset, including the potentially added one in
+			// this extrinsic.
+			let mut ongoing_bets: Vec<BetDataOf<T>> = OngoingBets::<T>::iter_prefix_values(round)
+				.filter(|bet_data| bet_data.asset == asset)
+				.collect::<Vec<_>>();
+			ongoing_bets.push(bet_data.clone());
+
+			// Amount that the pallet can lose in the worst case scenario.
+			let max_payout = Self::max_payout(ongoing_bets);
+
+			// Pallet account.
+			let account_id = Self::account_id();
+
+			// Hold the stake in the pallet account until the bet is settled.
+			if voucher.is_none() {
+				Self::transfer(asset, &sender, &account_id, amount)?;
+			}
+
+			// Verify that the house has enough free balance in the asset, not including the
+			// existential deposit, to cover this round and the ones waiting to be played.
+			let exposure = Self::total_exposure(asset)
+				.saturating_sub(Exposure::<T>::get(asset, round))
+				.saturating_add(max_payout);
+			ensure!(
+				Self::house_balance(asset) >= exposure,
+				Error::<T>::NotEnoughBalanceInPalletAccount
+			);
+
+			// Keep track of the house exposure in this asset.
+			Exposure::<T>::insert(asset, round, max_payout);
+
+			// The first bet of a round opens its betting window.
+			if !RoundEnds::<T>::contains_key(round) {
+				let ends_at = current_block
+					.saturating_add(T::BettingPeriod::get())
+					.saturating_sub(One::one());
+				RoundEnds::<T>::insert(round, ends_at);
+			}
+
+			// Store the bet.
+			OngoingBetsByOwner::<T>::insert(&bet_data.owner, bet_id, round);
+			OngoingBets::<T>::insert(round, bet_id, bet_data);
+			RoundBetCounts::<T>::mutate(round, |count| *count = count.saturating_add(1));
+
+			// Emit an event showing that the claim was created.
+			Self::deposit_event(Event::BetPlaced {
+				round,
+				who: sender,
+				bet_id,
+				asset,
+				amount,
+				bet,
+			});
+
+			Ok(bet_id)
+		}
+
+		/// Places a bet on behalf of `who`, such as a contract or a standing order, keeping
+		/// nothing from it if it fails. Returns the id of the bet.
+		pub fn try_place_bet(
+			who: T::AccountId,
+			asset: T::AssetId,
+			amount: T::Balance,
+			bet: PickOf<T>,
+		) -> Result<u64, DispatchError> {
+			with_transaction(|| match Self::do_place_bet(who, asset, amount, bet, None) {
+				Ok(bet_id) => TransactionOutcome::Commit(Ok(bet_id)),
+				Err(error) => TransactionOutcome::Rollback(Err(error)),
+			})
+		}
+
+		/// Places a bet on behalf of `who` like `try_place_bet`, and calls `selector` of `who`
+		/// back once it is settled. Meant for bets placed by contracts, which pay `CallbackFee`
+		/// for the callback. Returns the id of the bet.
+		pub fn try_place_bet_with_callback(
+			who: T::AccountId,
+			asset: T::AssetId,
+			amount: T::Balance,
+			bet: PickOf<T>,
+			selector: [u8; 4],
+		) -> Result<u64, DispatchError> {
+			with_transaction(|| {
+				let placed =
+					Self::do_place_bet(who.clone(), asset, amount, bet, None).and_then(|bet_id| {
+						let fee = T::CallbackFee::get();
+						let native = T::NativeAssetId::get();
+						ensure!(
+							Self::reducible_balance(native, &who) >= fee,
+							Error::<T>::NotEnoughBalance
+						);
+						Self::transfer(native, &who, &Self::account_id(), fee)?;
+						BetCallbacks::<T>::insert(bet_id, selector);
+						Ok(bet_id)
+					});
+				match placed {
+					Ok(bet_id) => TransactionOutcome::Commit(Ok(bet_id)),
+					Err(error) => TransactionOutcome::Rollback(Err(error)),
+				}
+			})
+		}
+
+		/// Pays the winnings credited to `who` in `asset` on its behalf, such as a contract whose
+		/// winnings are not pushed. Returns the amount paid.
+		pub fn try_claim_winnings(
+			who: &T::AccountId,
+			asset: T::AssetId,
+		) -> Result<T::Balance, DispatchError> {
+			Self::pay_pending(who, asset)
+		}
+
+		/// Bets of `who` that have not been played yet, with the round that takes them, in the
+		/// order they were placed. Only `limit` bets of `who` are looked at, each costing two
+		/// storage reads.
+		pub fn ongoing_bets_of(who: &T::AccountId, limit: u32) -> Vec<(u32, BetDataOf<T>)> {
+			let mut bets: Vec<(u32, BetDataOf<T>)> = OngoingBetsByOwner::<T>::iter_prefix(who)
+				.take(limit as usize)
+				.filter_map(|(bet_id, round)| {
+					OngoingBets::<T>::get(round, bet_id).map(|bet_data| (round, bet_data))
+				})
+				.collect();
+			bets.sort_by_key(|(_, bet_data)| bet_data.id);
+			bets
+		}
+
+		/// Storage reads of placing a bet that grow with the bets in play: every bet of the round
+		/// taking bets and the exposure of every round waiting to be played.
+		pub fn bets_in_play_reads() -> u64 {
+			let round = RoundIndex::<T>::get();
+			let rounds_in_play =
+				round.saturating_sub(NextRoundToPlay::<T>::get()).saturating_add(1);
+			u64::from(RoundBetCounts::<T>::get(round)).saturating_add(u64::from(rounds_in_play))
+		}
+
+		/// Summary of a bet that was already played.
+		pub fn settled_bet(bet_id: u64) -> Option<SettledBetOf<T>> {
+			Bets::<T>::get(bet_id)
+		}
+
+		/// Spin that played a round.
+		pub fn spin_of(round: u32) -> Option<SpinRecord<T::BlockNumber>> {
+			Spins::<T>::get(round)
+		}
+
+		/// Payout multiple of a new bet, from the payout table of the lottery if it overrides the
+		/// multiple of the game.
+		fn payout_multiple(pick: &PickOf<T>) -> u32 {
+			PayoutMultiples::<T>::get(T::Game::kind(pick))
+				.unwrap_or_else(|| T::Game::payout_multiple(pick))
+		}
+
+		/// Amount that a new bet can win.
+		pub(crate) fn amount_won(pick: &PickOf<T>, amount: T::Balance) -> T::Balance {
+			amount.saturating_mul(T::Balance::from(Self::payout_multiple(pick)))
 		}
 
-		/// Amount that a given bet can win.
-		fn amount_won(pick: Bet, amount: T::Balance) -> T::Balance {
-			match pick {
-				Bet::Color(_) => amount.saturating_mul(T::Balance::from(2_u32)),
-				Bet::Full(_) => amount.saturating_mul(T::Balance::from(36_u32)),
-				Bet::Dozen(_) => amount.saturating_mul(T::Balance::from(3_u32)),
-				Bet::Column(_) => amount.saturating_mul(T::Balance::from(3_u32)),
-				Bet::Half(_) => amount.saturating_mul(T::Balance::from(2_u32)),
-				Bet::OddOrEven(_) => amount.saturating_mul(T::Balance::from(2_u32)),
+		/// Amount paid to a bet if it wins, with the multiple it was placed with. Bets placed
+		/// with a voucher are only paid their profit, as their stake was not paid.
+		fn bet_payout(bet_data: &BetDataOf<T>) -> T::Balance {
+			let payout = bet_data.amount.saturating_mul(T::Balance::from(bet_data.multiple));
+			match bet_data.voucher {
+				Some(_) => payout.saturating_sub(bet_data.amount),
+				None => payout,
 			}
 		}
 
 		/// Maximum mount that the pallet can lose given a set of bets.
-		fn max_payout(bets: Vec<BetData<T::AccountId, T::BlockNumber, T::Balance>>) -> T::Balance {
+		fn max_payout(bets: Vec<BetDataOf<T>>) -> T::Balance {
 			let mut max = T::Balance::zero();
-			for winner_number in 0_u32..=36_u32 {
+			for winner_number in 0_u32..T::Game::outcomes() {
 				let total_payout = bets.iter().fold(T::Balance::zero(), |acc, bet_data| {
 					let mut payout = T::Balance::zero();
-					let is_winner = Self::is_winner(bet_data.bet.clone(), winner_number);
+					let is_winner = T::Game::is_winner(&bet_data.bet, winner_number);
 					if is_winner {
-						payout = Self::amount_won(bet_data.bet.clone(), bet_data.amount);
+						payout = Self::bet_payout(bet_data);
 					}
 					acc + payout
 				});
@@ -356,7 +2252,6 @@ pub mod pallet {
 					max = total_payout;
 				}
 			}
-			log::info!("max -> {:?}", max);
 			max
 		}
 	}
@@ -0,0 +1,142 @@
+//! Storage migrations of the lottery, run by the runtime on upgrade. Every migration only runs on
+//! chains whose on-chain storage version is below the one it migrates to.
+
+pub mod v1 {
+	//! Migration from the first layout of the roulette, in which every block played the bets
+	//! placed in the previous one. Stakes were locked in the accounts of their owners rather than
+	//! taken, and the house locked the worst case payout of the bets in play. `Bets` kept every
+	//! settled bet as it was placed, and `OngoingBets` was keyed by bet id only.
+
+	use crate::pallet::{Config, Pallet};
+	use codec::{Decode, Encode};
+	#[cfg(feature = "try-runtime")]
+	use frame_support::inherent::Vec;
+	use frame_support::{
+		pallet_prelude::*,
+		storage_alias,
+		traits::{LockIdentifier, LockableCurrency, OnRuntimeUpgrade, StorageVersion},
+	};
+	use traits::Bet;
+
+	/// Lock of the stakes and of the house payouts in the first layout.
+	const LOCK_ID: LockIdentifier = *b"roulette";
+
+	/// Bet as stored in the first layout.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct OldBetData<AccountId, BlockNumber, Balance> {
+		/// Bet id.
+		pub id: u64,
+		/// Owner of the bet.
+		pub owner: AccountId,
+		/// Bet amount, locked in the account of the owner.
+		pub amount: Balance,
+		/// Block in which bet occurs.
+		pub block: BlockNumber,
+		/// Type of bet.
+		pub bet: Bet,
+	}
+
+	/// Bet as stored by the first layout of this pallet.
+	pub type OldBetDataOf<T> = OldBetData<
+		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
+		<T as Config>::Balance,
+	>;
+
+	/// Bets in play in the first layout, by bet id.
+	#[storage_alias]
+	pub type OngoingBets<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, u64, OldBetDataOf<T>>;
+
+	/// Settled bets in the first layout, by bet id.
+	#[storage_alias]
+	pub type Bets<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, u64, OldBetDataOf<T>>;
+
+	/// Refunds the bets in play by lifting the locks on their stakes and on the house, and
+	/// removes the settled bets, whose outcome and payout were never stored and were only
+	/// announced in `RoulettePlayed` events. `Locks` is the native currency that held the locks.
+	pub struct MigrateToV1<T, Locks>(PhantomData<(T, Locks)>);
+
+	impl<T: Config, Locks: LockableCurrency<T::AccountId>> OnRuntimeUpgrade for MigrateToV1<T, Locks> {
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain = Pallet::<T>::on_chain_storage_version();
+			if on_chain >= 1 {
+				log::info!(
+					target: "runtime::lottery",
+					"Skipping the migration to v1, the storage is already at {:?}",
+					on_chain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			// Every lock removal reads and writes the locks and the account of its owner.
+			let mut refunded = 0_u64;
+			for (_, bet) in OngoingBets::<T>::drain() {
+				Locks::remove_lock(LOCK_ID, &bet.owner);
+				refunded = refunded.saturating_add(1);
+			}
+			Locks::remove_lock(LOCK_ID, &Pallet::<T>::account_id());
+			let removed = Bets::<T>::drain().count() as u64;
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(
+				target: "runtime::lottery",
+				"Migrated to v1: refunded {} bets in play and removed {} settled bets",
+				refunded,
+				removed,
+			);
+
+			let locks = refunded.saturating_add(1);
+			T::DbWeight::get().reads_writes(
+				locks.saturating_mul(2).saturating_add(removed).saturating_add(1),
+				locks.saturating_mul(3).saturating_add(removed).saturating_add(1),
+			)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			if Pallet::<T>::on_chain_storage_version() >= 1 {
+				return Ok(None::<(u32, u32)>.encode())
+			}
+
+			// Every entry must decode in the first layout, or it would be dropped unrefunded.
+			let ongoing = OngoingBets::<T>::iter_keys().count() as u32;
+			ensure!(
+				OngoingBets::<T>::iter_values().count() as u32 == ongoing,
+				"Some bets in play do not decode in the first layout"
+			);
+			let settled = Bets::<T>::iter_keys().count() as u32;
+			ensure!(
+				Bets::<T>::iter_values().count() as u32 == settled,
+				"Some settled bets do not decode in the first layout"
+			);
+			Ok(Some((ongoing, settled)).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 1,
+				"The storage version was not set to v1"
+			);
+			let migrated = Option::<(u32, u32)>::decode(&mut &state[..])
+				.map_err(|_| "Cannot decode the state saved before the migration")?;
+			if let Some((ongoing, settled)) = migrated {
+				ensure!(
+					OngoingBets::<T>::iter_keys().next().is_none(),
+					"Bets in play of the first layout are left"
+				);
+				ensure!(
+					Bets::<T>::iter_keys().next().is_none(),
+					"Settled bets of the first layout are left"
+				);
+				log::info!(
+					target: "runtime::lottery",
+					"Migrated {} bets in play and {} settled bets to v1",
+					ongoing,
+					settled,
+				);
+			}
+			Ok(())
+		}
+	}
+}
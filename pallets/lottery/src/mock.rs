@@ -3,21 +3,28 @@
 use crate as lottery;
 use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{ConstU32, ConstU64, Everything, OnFinalize, OnInitialize},
+	traits::{ConstU32, ConstU64, Everything, OnFinalize, OnInitialize, UnixTime},
+	weights::Weight,
 	PalletId,
 };
-use frame_support_test::TestRandomness;
 use frame_system::{
 	mocking::{MockBlock, MockUncheckedExtrinsic},
-	Config,
+	Config, EnsureRoot,
 };
 pub use pallet_balances::Call as BalancesCall;
-use sp_runtime::generic::Header;
-use sp_runtime::testing::H256;
-use sp_runtime::traits::{BlakeTwo256, IdentityLookup};
+use sp_core::{ed25519, Pair};
+use sp_runtime::{
+	generic::Header,
+	testing::H256,
+	traits::{AccountIdConversion, BlakeTwo256, Hash, IdentityLookup},
+	Perbill,
+};
+
+use std::{cell::RefCell, collections::BTreeMap, time::Duration};
+use traits::{BetCallback, BetSettlement, RandomnessRequest, RandomnessSource};
 
 type Block = MockBlock<Test>;
-type UncheckedExtrinsic = MockUncheckedExtrinsic<Test>;
+pub type UncheckedExtrinsic = MockUncheckedExtrinsic<Test>;
 
 construct_runtime!(
  pub enum Test where
@@ -26,20 +33,176 @@ construct_runtime!(
   UncheckedExtrinsic = UncheckedExtrinsic,
  {
    System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-   Lottery: lottery::{Pallet, Call, Storage, Event<T>},
+   Lottery: lottery::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
 	 Balances: pallet_balances::{Pallet, Call, Storage, Event<T>},
+	 Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
  }
 );
 
 parameter_types! {
   pub const RoulettePalletId: PalletId = PalletId(*b"roulette");
+  pub const CallbackWeight: Weight = Weight::from_ref_time(1_000_000);
+  pub PrizeTiers: Vec<Perbill> = vec![Perbill::from_percent(50), Perbill::from_percent(30)];
+  pub const JackpotContribution: Perbill = Perbill::from_percent(10);
+  pub const JackpotPayout: Perbill = Perbill::from_percent(50);
+  pub static PullPayouts: bool = false;
+  pub const ReferralShare: Perbill = Perbill::from_percent(20);
+}
+
+/// Key pair of the randomness beacon.
+pub fn beacon_pair() -> ed25519::Pair {
+	ed25519::Pair::from_seed(&[1; 32])
+}
+
+thread_local! {
+	static RANDOM_SEED: RefCell<H256> = RefCell::new(H256::zero());
+	static RIGGED_SEEDS: RefCell<BTreeMap<u32, H256>> = RefCell::new(BTreeMap::new());
+	static UNIX_TIME: RefCell<u64> = RefCell::new(0);
+	static CALLBACKS: RefCell<Vec<(u128, [u8; 4], BetSettlement<u64>)>> = RefCell::new(Vec::new());
+}
+
+/// Clock whose time is set by the tests.
+pub struct MockTime;
+
+impl UnixTime for MockTime {
+	fn now() -> Duration {
+		Duration::from_secs(UNIX_TIME.with(|time| *time.borrow()))
+	}
+}
+
+/// Sets the time of the clock, in seconds.
+pub fn set_unix_time(secs: u64) {
+	UNIX_TIME.with(|time| *time.borrow_mut() = secs);
+}
+
+/// Random seed set by the tests, which becomes the hash of every block they advance past that no
+/// rigged round is bound to.
+pub fn random_seed() -> H256 {
+	RANDOM_SEED.with(|seed| *seed.borrow())
+}
+
+/// Hash of a block once the tests advance past it: the seed rigged for the round bound to it, if
+/// any, or the random seed.
+fn block_hash(block: u32) -> H256 {
+	let rigged = RIGGED_SEEDS.with(|seeds| seeds.borrow().clone());
+	rigged
+		.into_iter()
+		.find(|(round, _)| {
+			Lottery::bound_randomness(&Lottery::spin_subject(*round)) ==
+				Some(RandomnessRequest::BlockHash(block))
+		})
+		.map(|(_, seed)| seed)
+		.unwrap_or_else(random_seed)
+}
+
+/// Records the callbacks of settled bets instead of calling contracts.
+pub struct MockCallback;
+
+impl BetCallback<u128, u64> for MockCallback {
+	fn on_settled(
+		who: &u128,
+		selector: [u8; 4],
+		settlement: BetSettlement<u64>,
+		weight_limit: Weight,
+	) -> Weight {
+		CALLBACKS.with(|callbacks| callbacks.borrow_mut().push((*who, selector, settlement)));
+		weight_limit
+	}
 }
 
+/// Callbacks made so far.
+pub fn callbacks() -> Vec<(u128, [u8; 4], BetSettlement<u64>)> {
+	CALLBACKS.with(|callbacks| callbacks.borrow().clone())
+}
+
+/// Rigs the roulette spin of `round` to land on `outcome`, making the hash of the block that the
+/// round is bound to one whose `LotteryRandomness` for the round lands on it.
+pub fn rig_spin(round: u32, outcome: u32) {
+	let subject = Lottery::spin_subject(round);
+	let seed = (0_u32..)
+		.map(|nonce| BlakeTwo256::hash_of(&nonce))
+		.find(|seed| {
+			let randomness = BlakeTwo256::hash_of(&(seed, &subject));
+			traits::verify_spin(randomness.as_ref(), &subject) == outcome
+		})
+		.expect("every outcome is eventually hit");
+	RIGGED_SEEDS.with(|seeds| seeds.borrow_mut().insert(round, seed));
+}
+
+/// Asset id used for bets in the native currency.
+pub const NATIVE: u32 = 0;
+/// Asset id of a stablecoin registered in `pallet_assets`.
+pub const STABLE: u32 = 1;
+
 impl lottery::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
-	type LotteryRandomness = TestRandomness<Self>;
+	type Balance = u64;
+	type AssetId = u32;
+	type LotteryRandomness = lottery::BeaconRandomness<Test>;
+	type Game = traits::Roulette;
 	type Currency = Balances;
+	type Assets = Assets;
+	type NativeAssetId = ConstU32<NATIVE>;
 	type PalletId = RoulettePalletId;
+	type TicketPrice = ConstU64<10>;
+	type NumbersPerTicket = ConstU32<3>;
+	type MaxTicketNumber = ConstU32<10>;
+	type DrawPeriod = ConstU32<5>;
+	type MaxTicketsPerDraw = ConstU32<10>;
+	type PrizeTiers = PrizeTiers;
+	type JackpotContribution = JackpotContribution;
+	type JackpotPayout = JackpotPayout;
+	type JackpotStreak = ConstU32<2>;
+	type BettingPeriod = ConstU32<1>;
+	type RandomnessDelay = ConstU32<1>;
+	type CommitmentDeposit = ConstU64<5>;
+	type RevealPeriod = ConstU32<2>;
+	type MaxCommitments = ConstU32<4>;
+	type UnixTime = MockTime;
+	type MaxBeaconUrlLength = ConstU32<64>;
+	type BeaconUnsignedPriority = ConstU64<{ 1 << 20 }>;
+	type BetPriority = ConstU64<{ 1 << 10 }>;
+	type PullPayouts = PullPayouts;
+	type ClaimPeriod = ConstU32<3>;
+	type MaxExpiriesPerBlock = ConstU32<2>;
+	type ReferralShare = ReferralShare;
+	type MaxReferralCodeLength = ConstU32<8>;
+	type AdminOrigin = EnsureRoot<u128>;
+	type MaxVoucherBetKinds = ConstU32<3>;
+	type MaxVoucherExpiries = ConstU32<2>;
+	type MaxScheduledBets = ConstU32<2>;
+	type MaxStrategies = ConstU32<2>;
+	type BetCallback = MockCallback;
+	type CallbackWeight = CallbackWeight;
+	type CallbackFee = ConstU64<1>;
+	type MaxCallbacksPerBlock = ConstU32<2>;
+	type MaxRoundsPerBlock = ConstU32<2>;
+	type MaxBetsPerBlock = ConstU32<8>;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
+impl pallet_assets::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<u128>;
+	type AssetDeposit = ConstU64<1>;
+	type AssetAccountDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type ApprovalDeposit = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
 }
 
 /// Existential deposit.
@@ -71,7 +234,8 @@ impl Config for Test {
 	type BlockNumber = u32;
 	type Hash = H256;
 	type Hashing = BlakeTwo256;
-	type AccountId = u64;
+	// Wide enough for pallet sub-accounts not to be truncated into the pallet account.
+	type AccountId = u128;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header<Self::BlockNumber, BlakeTwo256>;
 	type RuntimeEvent = RuntimeEvent;
@@ -88,16 +252,44 @@ impl Config for Test {
 	type MaxConsumers = ConstU32<16>;
 }
 
+/// Account of the lottery pallet, which acts as the house.
+pub fn pallet_account() -> u128 {
+	RoulettePalletId::get().into_account_truncating()
+}
+
+/// Account holding the pot of the ticket lottery.
+pub fn draw_account() -> u128 {
+	RoulettePalletId::get().into_sub_account_truncating(b"draw")
+}
+
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 	pallet_balances::GenesisConfig::<Test> {
-		balances: vec![(1, 100), (2, 100), (3, 100), (4, 100), (5, 100)],
+		balances: vec![
+			(1, 100),
+			(2, 100),
+			(3, 100),
+			(4, 100),
+			(5, 100),
+			(pallet_account(), 10_000),
+			(draw_account(), EXISTENTIAL_DEPOSIT),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_assets::GenesisConfig::<Test> {
+		assets: vec![(STABLE, pallet_account(), true, 1)],
+		metadata: vec![],
+		accounts: vec![(STABLE, 1, 1_000), (STABLE, 2, 1_000), (STABLE, pallet_account(), 1_000)],
 	}
 	.assimilate_storage(&mut t)
 	.unwrap();
 
 	let mut ext = sp_io::TestExternalities::new(t);
-	ext.execute_with(|| System::set_block_number(1));
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		lottery::SourceOfRandomness::<Test>::put(RandomnessSource::BlockHash);
+	});
 	ext
 }
 
@@ -107,6 +299,10 @@ pub fn run_to_block(n: u64) {
 			Lottery::on_finalize(System::block_number());
 			System::on_finalize(System::block_number());
 		}
+		frame_system::BlockHash::<Test>::insert(
+			System::block_number(),
+			block_hash(System::block_number()),
+		);
 		System::set_block_number(System::block_number() + 1);
 		System::on_initialize(System::block_number());
 		Lottery::on_initialize(System::block_number());
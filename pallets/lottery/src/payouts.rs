@@ -0,0 +1,101 @@
+//! Pull-based payouts: winnings credited at settlement wait in `PendingPayouts` until they are
+//! claimed, and go back to the house once `ClaimPeriod` is over.
+
+use crate::pallet::{
+	Config, Error, Event, Pallet, PayoutExpiries, PayoutExpiryBacklog, PendingPayouts,
+	PendingTotals,
+};
+use frame_support::pallet_prelude::*;
+use sp_runtime::{traits::One, Saturating};
+use traits::PendingPayout;
+
+impl<T: Config> Pallet<T> {
+	/// Credits `amount` of `asset` to `who`, pushing back the expiry of its pending winnings.
+	pub(crate) fn credit_payout(
+		who: &T::AccountId,
+		asset: T::AssetId,
+		amount: T::Balance,
+		now: T::BlockNumber,
+	) {
+		let expires_at = now.saturating_add(T::ClaimPeriod::get());
+		let pending = PendingPayouts::<T>::mutate(who, asset, |pending| {
+			let amount =
+				pending.as_ref().map_or(amount, |pending| pending.amount.saturating_add(amount));
+			*pending = Some(PendingPayout { amount, expires_at });
+			amount
+		});
+		PendingTotals::<T>::mutate(asset, |total| *total = total.saturating_add(amount));
+		PayoutExpiries::<T>::insert(expires_at, (who.clone(), asset), ());
+
+		Self::deposit_event(Event::PayoutCredited {
+			who: who.clone(),
+			asset,
+			amount,
+			pending,
+			expires_at,
+		});
+	}
+
+	/// Pays the pending winnings of `who` in `asset`, returning the amount paid.
+	pub(crate) fn pay_pending(
+		who: &T::AccountId,
+		asset: T::AssetId,
+	) -> Result<T::Balance, DispatchError> {
+		let pending = PendingPayouts::<T>::get(who, asset).ok_or(Error::<T>::NothingToClaim)?;
+
+		Self::transfer(asset, &Self::account_id(), who, pending.amount)?;
+		PendingPayouts::<T>::remove(who, asset);
+		PendingTotals::<T>::mutate(asset, |total| *total = total.saturating_sub(pending.amount));
+
+		Self::deposit_event(Event::WinningsClaimed {
+			who: who.clone(),
+			asset,
+			amount: pending.amount,
+		});
+
+		Ok(pending.amount)
+	}
+
+	/// Returns to the house the winnings that expired by `now`, up to `MaxExpiriesPerBlock` of
+	/// them. Blocks whose winnings were not all returned are carried forward to the next block.
+	pub(crate) fn expire_payouts(now: T::BlockNumber) -> Weight {
+		let mut weight = T::DbWeight::get().reads_writes(1, 1);
+		let mut block = PayoutExpiryBacklog::<T>::get().unwrap_or(now);
+		let mut expiries_left = T::MaxExpiriesPerBlock::get();
+		while block <= now {
+			weight = weight.saturating_add(T::DbWeight::get().reads(1));
+			for ((who, asset), ()) in
+				PayoutExpiries::<T>::drain_prefix(block).take(expiries_left as usize)
+			{
+				expiries_left -= 1;
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+
+				// Winnings credited again since this entry was added expire later.
+				let pending = match PendingPayouts::<T>::get(&who, asset) {
+					Some(pending) if pending.expires_at == block => pending,
+					_ => continue,
+				};
+
+				PendingPayouts::<T>::remove(&who, asset);
+				PendingTotals::<T>::mutate(asset, |total| {
+					*total = total.saturating_sub(pending.amount)
+				});
+				weight = weight.saturating_add(T::DbWeight::get().writes(2));
+
+				Self::deposit_event(Event::PayoutExpired { who, asset, amount: pending.amount });
+			}
+			// The block may still have winnings to return.
+			if expiries_left == 0 {
+				break
+			}
+			block = block.saturating_add(One::one());
+		}
+
+		if block <= now {
+			PayoutExpiryBacklog::<T>::put(block);
+		} else {
+			PayoutExpiryBacklog::<T>::kill();
+		}
+		weight
+	}
+}
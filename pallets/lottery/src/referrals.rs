@@ -0,0 +1,93 @@
+//! Referral program: players bind once to the referrer whose code they use, and referrers earn
+//! `ReferralShare` of the net income of the house from those players, computed on every
+//! settlement and claimable at any time.
+
+use crate::pallet::{
+	Config, Event, Pallet, ReferralRewards, ReferralStatistics, ReferralTotals, Referrers,
+};
+use frame_support::{inherent::Vec, traits::Get, weights::Weight};
+use sp_runtime::{traits::Zero, Saturating};
+
+/// Stakes and payouts of a referred player in a single asset during a round.
+pub(crate) struct ReferredResult<AccountId, AssetId, Balance> {
+	who: AccountId,
+	asset: AssetId,
+	staked: Balance,
+	paid: Balance,
+}
+
+/// Results of the referred players of a round, as kept by the pallet.
+pub(crate) type ReferredResultsOf<T> = Vec<
+	ReferredResult<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::AssetId,
+		<T as Config>::Balance,
+	>,
+>;
+
+impl<T: Config> Pallet<T> {
+	/// Adds the stake and the payout of a settled bet to the results of its owner, if the owner
+	/// was referred.
+	pub(crate) fn record_referred_bet(
+		results: &mut ReferredResultsOf<T>,
+		who: &T::AccountId,
+		asset: T::AssetId,
+		staked: T::Balance,
+		paid: T::Balance,
+	) {
+		if !Referrers::<T>::contains_key(who) {
+			return
+		}
+
+		match results.iter_mut().find(|result| &result.who == who && result.asset == asset) {
+			Some(result) => {
+				result.staked = result.staked.saturating_add(staked);
+				result.paid = result.paid.saturating_add(paid);
+			},
+			None => results.push(ReferredResult { who: who.clone(), asset, staked, paid }),
+		}
+	}
+
+	/// Accrues to every referrer its share of the net income of the house from its players.
+	pub(crate) fn reward_referrers(round: u32, results: ReferredResultsOf<T>) -> Weight {
+		let mut weight = Weight::zero();
+		for result in results {
+			// The house only shares what it won.
+			if result.staked <= result.paid {
+				continue
+			}
+			weight = weight.saturating_add(T::DbWeight::get().reads(1));
+			let referrer = match Referrers::<T>::get(&result.who) {
+				Some(referrer) => referrer,
+				None => continue,
+			};
+
+			let income = result.staked.saturating_sub(result.paid);
+			let reward = T::ReferralShare::get() * income;
+			if reward.is_zero() {
+				continue
+			}
+
+			ReferralStatistics::<T>::mutate(&referrer, result.asset, |stats| {
+				stats.income = stats.income.saturating_add(income);
+				stats.rewards = stats.rewards.saturating_add(reward);
+			});
+			ReferralRewards::<T>::mutate(&referrer, result.asset, |rewards| {
+				*rewards = rewards.saturating_add(reward)
+			});
+			ReferralTotals::<T>::mutate(result.asset, |total| {
+				*total = total.saturating_add(reward)
+			});
+
+			Self::deposit_event(Event::ReferralRewarded {
+				round,
+				referrer,
+				player: result.who,
+				asset: result.asset,
+				reward,
+			});
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(3, 4));
+		}
+		weight
+	}
+}
@@ -0,0 +1,50 @@
+//! Scheduled bets: standing orders that the pallet places on behalf of their owners at the start
+//! of every round, with the same checks as `place_bet`, until they run out of rounds or budget.
+
+use crate::pallet::{Config, Event, Pallet, ScheduledBetOf, ScheduledBets};
+use frame_support::{inherent::Vec, pallet_prelude::*};
+use sp_runtime::Saturating;
+
+impl<T: Config> Pallet<T> {
+	/// Keeps a schedule while it can place another bet, and ends it otherwise.
+	pub(crate) fn store_schedule(schedule_id: u64, schedule: ScheduledBetOf<T>) {
+		if schedule.rounds > 0 && schedule.budget >= schedule.amount {
+			ScheduledBets::<T>::insert(schedule_id, schedule);
+		} else {
+			ScheduledBets::<T>::remove(schedule_id);
+			Self::deposit_event(Event::ScheduleEnded { schedule_id });
+		}
+	}
+
+	/// Places the scheduled bets in the round that just started. Schedules whose bet cannot be
+	/// placed end.
+	pub(crate) fn place_scheduled_bets() -> Weight {
+		let schedules = ScheduledBets::<T>::iter().collect::<Vec<_>>();
+		let mut weight = T::DbWeight::get().reads(schedules.len() as u64);
+
+		for (schedule_id, mut schedule) in schedules {
+			let placed = Self::try_place_bet(
+				schedule.owner.clone(),
+				schedule.asset,
+				schedule.amount,
+				schedule.bet.clone(),
+			);
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(8, 6));
+
+			match placed {
+				Ok(_) => {
+					schedule.rounds -= 1;
+					schedule.budget = schedule.budget.saturating_sub(schedule.amount);
+					Self::store_schedule(schedule_id, schedule);
+				},
+				Err(error) => {
+					Self::deposit_event(Event::ScheduledBetFailed { schedule_id, error });
+					ScheduledBets::<T>::remove(schedule_id);
+					Self::deposit_event(Event::ScheduleEnded { schedule_id });
+				},
+			}
+		}
+
+		weight
+	}
+}
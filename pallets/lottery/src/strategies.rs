@@ -0,0 +1,79 @@
+//! Betting strategies: once the bet of a strategy is settled, the pallet moves its progression
+//! to the next step and places the next stake in the round taking bets, until the stop-loss or
+//! the take-profit of the strategy is hit.
+
+use crate::pallet::{Config, Event, Pallet, Strategies, StrategyBets, StrategyOf};
+use frame_support::{inherent::Vec, traits::Get, weights::Weight};
+use sp_runtime::Saturating;
+use traits::StopReason;
+
+impl<T: Config> Pallet<T> {
+	/// Moves the strategies whose bets were settled to their next step, given what every bet
+	/// staked and was paid.
+	pub(crate) fn advance_strategies(results: Vec<(u64, T::Balance, T::Balance)>) -> Weight {
+		let mut weight = Weight::zero();
+		for (strategy_id, staked, paid) in results {
+			weight = weight.saturating_add(T::DbWeight::get().reads(1));
+			// Cancelled strategies still have their last bet played.
+			let mut strategy = match Strategies::<T>::get(strategy_id) {
+				Some(strategy) => strategy,
+				None => continue,
+			};
+			strategy.staked = strategy.staked.saturating_add(staked);
+			strategy.paid = strategy.paid.saturating_add(paid);
+			strategy.step = strategy.progression.next_step(strategy.step, paid > staked);
+
+			let stake = strategy
+				.base_stake
+				.saturating_mul(T::Balance::from(strategy.progression.units(strategy.step)));
+			let loss = strategy.staked.saturating_sub(strategy.paid);
+			// Stopping removes the strategy, and every step places a bet as scheduled bets do.
+			if strategy.paid.saturating_sub(strategy.staked) >= strategy.take_profit {
+				weight = weight.saturating_add(T::DbWeight::get().writes(2));
+				Self::stop_strategy(strategy_id, strategy, StopReason::TakeProfit);
+				continue
+			}
+			if loss.saturating_add(stake) > strategy.stop_loss {
+				weight = weight.saturating_add(T::DbWeight::get().writes(2));
+				Self::stop_strategy(strategy_id, strategy, StopReason::StopLoss);
+				continue
+			}
+
+			let placed = Self::try_place_bet(
+				strategy.owner.clone(),
+				strategy.asset,
+				stake,
+				strategy.bet.clone(),
+			);
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(8, 9));
+			match placed {
+				Ok(bet_id) => {
+					StrategyBets::<T>::insert(bet_id, strategy_id);
+					Self::deposit_event(Event::StrategyStep {
+						strategy_id,
+						bet_id,
+						step: strategy.step,
+						stake,
+					});
+					Strategies::<T>::insert(strategy_id, strategy);
+				},
+				Err(error) => {
+					Self::deposit_event(Event::StrategyBetFailed { strategy_id, error });
+					Self::stop_strategy(strategy_id, strategy, StopReason::Failed);
+				},
+			}
+		}
+		weight
+	}
+
+	/// Removes a strategy, reporting what it staked and was paid.
+	pub(crate) fn stop_strategy(strategy_id: u64, strategy: StrategyOf<T>, reason: StopReason) {
+		Strategies::<T>::remove(strategy_id);
+		Self::deposit_event(Event::StrategyStopped {
+			strategy_id,
+			reason,
+			staked: strategy.staked,
+			paid: strategy.paid,
+		});
+	}
+}
@@ -1,4 +1,47 @@
-use crate::mock::{new_test_ext, run_to_block, System};
+use crate::{
+	account_bet_key, beacon_message, bet_record_key,
+	migrations::v1::{self, MigrateToV1, OldBetData},
+	mock::{
+		beacon_pair, callbacks, draw_account, new_test_ext, pallet_account, random_seed, rig_spin,
+		run_to_block, set_unix_time, Assets, Balances, CallbackWeight, Lottery, PullPayouts,
+		RuntimeCall, RuntimeEvent, RuntimeOrigin, System, Test, UncheckedExtrinsic, NATIVE, STABLE,
+	},
+	AwaitedBeaconRounds, BetRecordOf, CheckBet, CommitmentDeposits, DrawIndex, Error, Event,
+	Exposure, GenesisConfig, Jackpot, PendingPayouts, Pot, RandomnessSourceOf, ReferralCodeOf,
+	ReferralStatistics, ReferrerInfos, RoundIndex, ScheduledBets, SourceOfRandomness, Spins,
+	Strategies, TicketNumbersOf, VoucherExpiries, Vouchers, INVALID_BET, UNAFFORDABLE_BET,
+	UNCOVERED_BET,
+};
+use codec::Decode;
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::DispatchInfo,
+	traits::{
+		GenesisBuild, LockableCurrency, OffchainWorker, OnInitialize, OnRuntimeUpgrade,
+		StorageVersion, WithdrawReasons,
+	},
+	BoundedVec,
+};
+use sp_core::{
+	hashing::blake2_256,
+	hexdisplay::HexDisplay,
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, Timestamp, TransactionPoolExt},
+	Pair, H256,
+};
+use sp_runtime::{
+	traits::{BlakeTwo256, Hash, SignedExtension, ValidateUnsigned},
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	DispatchError,
+};
+use traits::{
+	BeaconInfo, Bet, BetKind, BetSettlement, Progression, RandomnessRequest, RandomnessSource,
+	ReferralStats, RouletteColor, StopReason,
+};
+
+/// `LotteryRandomness` of the game with `subject` once the randomness it is bound to is known.
+fn lottery_randomness(randomness: H256, subject: Vec<u8>) -> H256 {
+	BlakeTwo256::hash_of(&(randomness, subject))
+}
 
 #[test]
 fn fake_test_example() {
@@ -8,3 +51,1570 @@ fn fake_test_example() {
 		assert_eq!(System::block_number(), 5);
 	});
 }
+
+#[test]
+fn place_bet_holds_stake_in_native_currency() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			10,
+			Bet::Color(RouletteColor::Red)
+		));
+
+		assert_eq!(Balances::free_balance(1), 90);
+		assert_eq!(Balances::free_balance(pallet_account()), 10_010);
+		assert_eq!(Exposure::<Test>::get(NATIVE, 0), 20);
+		assert_eq!(Exposure::<Test>::get(STABLE, 0), 0);
+	});
+}
+
+#[test]
+fn place_bet_holds_stake_in_asset() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			STABLE,
+			100,
+			Bet::Color(RouletteColor::Red)
+		));
+
+		assert_eq!(Assets::balance(STABLE, 1), 900);
+		assert_eq!(Assets::balance(STABLE, pallet_account()), 1_100);
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(Exposure::<Test>::get(STABLE, 0), 200);
+	});
+}
+
+#[test]
+fn house_capacity_is_checked_per_asset() {
+	new_test_ext().execute_with(|| {
+		// The stablecoin bankroll cannot cover a straight-up win of 100.
+		assert_noop!(
+			Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 100, Bet::Full(5)),
+			Error::<Test>::NotEnoughBalanceInPalletAccount
+		);
+
+		// The native bankroll can.
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 40, Bet::Full(5)));
+	});
+}
+
+#[test]
+fn invalid_bets_are_kept_out_of_the_pool() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(1000)),
+			Error::<Test>::OutOfRange
+		);
+
+		let call =
+			|bet| RuntimeCall::Lottery(crate::Call::place_bet { asset: NATIVE, amount: 10, bet });
+		let info = DispatchInfo::default();
+		assert_eq!(
+			CheckBet::<Test>::new().validate(&1, &call(Bet::Full(1000)), &info, 0),
+			InvalidTransaction::Custom(INVALID_BET).into()
+		);
+		assert_ok!(CheckBet::<Test>::new().validate(&1, &call(Bet::Full(36)), &info, 0));
+	});
+}
+
+#[test]
+fn bets_that_would_fail_are_kept_out_of_the_pool() {
+	new_test_ext().execute_with(|| {
+		let call = |asset, amount, bet| {
+			RuntimeCall::Lottery(crate::Call::place_bet { asset, amount, bet })
+		};
+		let info = DispatchInfo::default();
+		let check = CheckBet::<Test>::new();
+
+		assert_eq!(
+			check.validate(&1, &call(NATIVE, 1_000, Bet::Full(5)), &info, 0),
+			InvalidTransaction::Custom(UNAFFORDABLE_BET).into()
+		);
+		// The stablecoin bankroll cannot cover a straight-up win of 3_600.
+		assert_eq!(
+			check.validate(&1, &call(STABLE, 100, Bet::Full(5)), &info, 0),
+			InvalidTransaction::Custom(UNCOVERED_BET).into()
+		);
+
+		// Bets live in the pool until the round closes.
+		let valid = check.validate(&1, &call(NATIVE, 40, Bet::Full(5)), &info, 0).unwrap();
+		assert_eq!((valid.priority, valid.longevity), (1 << 10, 1));
+	});
+}
+
+#[test]
+fn stakes_feed_the_jackpot() {
+	new_test_ext().execute_with(|| {
+		rig_spin(0, 0);
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			40,
+			Bet::Color(RouletteColor::Red)
+		));
+
+		run_to_block(4);
+
+		System::assert_has_event(RuntimeEvent::Lottery(Event::JackpotIncreased {
+			asset: NATIVE,
+			contribution: 4,
+			jackpot: 4,
+		}));
+		assert_eq!(Jackpot::<Test>::get(NATIVE), 4);
+		assert_eq!(Jackpot::<Test>::get(STABLE), 0);
+		assert_eq!(Lottery::house_balance(NATIVE), 10_040 - 50 - 4);
+	});
+}
+
+#[test]
+fn straight_up_streak_wins_jackpot() {
+	new_test_ext().execute_with(|| {
+		rig_spin(0, 0);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 40, Bet::Full(0)));
+		run_to_block(4);
+		assert_eq!(Jackpot::<Test>::get(NATIVE), 4);
+
+		rig_spin(1, 0);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 40, Bet::Full(0)));
+		run_to_block(7);
+
+		System::assert_last_event(RuntimeEvent::Lottery(Event::JackpotWon {
+			asset: NATIVE,
+			who: 1,
+			number: 0,
+			amount: 4,
+		}));
+		assert_eq!(Jackpot::<Test>::get(NATIVE), 4);
+	});
+}
+
+#[test]
+fn jackpot_streaks_are_kept_per_asset() {
+	new_test_ext().execute_with(|| {
+		let jackpot_won = |asset: u32| {
+			System::events().into_iter().any(|record| {
+				matches!(
+					record.event,
+					RuntimeEvent::Lottery(Event::JackpotWon { asset: won, .. }) if won == asset
+				)
+			})
+		};
+
+		rig_spin(0, 0);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 40, Bet::Full(0)));
+		run_to_block(4);
+
+		// Hitting the number again with another asset does not extend the streak.
+		rig_spin(1, 0);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 10, Bet::Full(0)));
+		run_to_block(7);
+		assert!(!jackpot_won(NATIVE));
+		assert!(!jackpot_won(STABLE));
+
+		// Only the asset hit in consecutive rounds wins its jackpot.
+		rig_spin(2, 0);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(0)));
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 10, Bet::Full(0)));
+		run_to_block(10);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::JackpotWon {
+			asset: STABLE,
+			who: 1,
+			number: 0,
+			amount: 1,
+		}));
+		assert!(!jackpot_won(NATIVE));
+		assert_eq!(Jackpot::<Test>::get(NATIVE), 5);
+		assert_eq!(Jackpot::<Test>::get(STABLE), 1);
+	});
+}
+
+#[test]
+fn settlement_reports_every_asset() {
+	new_test_ext().execute_with(|| {
+		rig_spin(0, 0);
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			10,
+			Bet::Color(RouletteColor::Red)
+		));
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(2),
+			STABLE,
+			100,
+			Bet::Color(RouletteColor::Black)
+		));
+
+		run_to_block(4);
+
+		// Zero is neither red nor black, so both color bets lose.
+		let seed = Spins::<Test>::get(0).unwrap().seed;
+		System::assert_has_event(RuntimeEvent::Lottery(Event::RoulettePlayed {
+			round: 0,
+			asset: NATIVE,
+			seed,
+			winner_number: 0,
+			players: 1,
+			income: 10,
+			payout: 0,
+		}));
+		System::assert_has_event(RuntimeEvent::Lottery(Event::RoulettePlayed {
+			round: 0,
+			asset: STABLE,
+			seed,
+			winner_number: 0,
+			players: 1,
+			income: 100,
+			payout: 0,
+		}));
+		assert_eq!(Assets::balance(STABLE, pallet_account()), 1_100);
+		assert_eq!(Exposure::<Test>::get(NATIVE, 0), 0);
+		assert_eq!(Exposure::<Test>::get(STABLE, 0), 0);
+	});
+}
+
+#[test]
+fn ongoing_bets_are_listed_per_owner() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..5 {
+			assert_ok!(Lottery::place_bet(
+				RuntimeOrigin::signed(2),
+				NATIVE,
+				10,
+				Bet::Color(RouletteColor::Red)
+			));
+		}
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+
+		// Bets of other players placed before do not count towards the limit.
+		let bets = Lottery::ongoing_bets_of(&1, 1);
+		assert_eq!(bets.len(), 1);
+		assert_eq!((bets[0].0, bets[0].1.owner, bets[0].1.id), (0, 1, 5));
+		assert_eq!(Lottery::ongoing_bets_of(&2, 3).len(), 3);
+
+		run_to_block(4);
+		assert!(Lottery::ongoing_bets_of(&1, 10).is_empty());
+		assert!(Lottery::ongoing_bets_of(&2, 10).is_empty());
+	});
+}
+
+#[test]
+fn spins_can_be_verified() {
+	new_test_ext().execute_with(|| {
+		rig_spin(0, 17);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+
+		run_to_block(4);
+
+		let spin = Spins::<Test>::get(0).unwrap();
+		assert_eq!(spin.outcome, 17);
+		assert_eq!(spin.subject.to_vec(), Lottery::spin_subject(0));
+		assert_eq!(traits::verify_spin(spin.seed.as_ref(), &spin.subject), 17);
+		assert_eq!(Balances::free_balance(1), 90 + 360);
+	});
+}
+
+#[test]
+fn rounds_are_bound_to_a_block_built_after_their_last_bet() {
+	new_test_ext().execute_with(|| {
+		// Round 0 takes bets until block 1, in which its last bet is placed.
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+
+		// It closes in block 2 and is bound to the hash of block 3, past the randomness delay.
+		run_to_block(2);
+		assert_eq!(
+			Lottery::bound_randomness(&Lottery::spin_subject(0)),
+			Some(RandomnessRequest::BlockHash(3))
+		);
+		run_to_block(3);
+		assert!(Spins::<Test>::get(0).is_none());
+
+		// The outcome is only fixed once block 3 is built, well after the bet was placed.
+		rig_spin(0, 17);
+		run_to_block(4);
+		let spin = Spins::<Test>::get(0).unwrap();
+		assert_eq!(spin.seed, lottery_randomness(System::block_hash(3), Lottery::spin_subject(0)));
+		assert_eq!(spin.outcome, 17);
+		assert_eq!(Balances::free_balance(1), 90 + 360);
+	});
+}
+
+#[test]
+fn every_bet_gets_a_settlement_event() {
+	new_test_ext().execute_with(|| {
+		rig_spin(0, 17);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(2), NATIVE, 10, Bet::Full(18)));
+
+		run_to_block(4);
+
+		let settled = |bet_id, who, won, payout| {
+			System::events().into_iter().any(|record| {
+				record.event ==
+					RuntimeEvent::Lottery(Event::BetSettled {
+						bet_id,
+						who,
+						won,
+						payout,
+						pending: false,
+					}) && record.topics == vec![BlakeTwo256::hash_of(&who)]
+			})
+		};
+		assert!(settled(0, 1, true, 360));
+		assert!(settled(1, 2, false, 0));
+	});
+}
+
+#[test]
+fn unpaid_bets_are_settled_pending() {
+	new_test_ext().execute_with(|| {
+		rig_spin(0, 17);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 10, Bet::Full(17)));
+
+		// Payouts cannot be made while the asset is frozen.
+		assert_ok!(Assets::freeze_asset(RuntimeOrigin::signed(pallet_account()), STABLE));
+		run_to_block(4);
+
+		System::assert_has_event(RuntimeEvent::Lottery(Event::BetSettled {
+			bet_id: 0,
+			who: 1,
+			won: true,
+			payout: 360,
+			pending: true,
+		}));
+		assert!(!System::events().into_iter().any(|record| matches!(
+			record.event,
+			RuntimeEvent::Lottery(Event::BetRejected { .. })
+		)));
+		assert_eq!(Assets::balance(STABLE, 1), 990);
+
+		// The payout is credited instead, so it can be claimed once the asset thaws.
+		assert_eq!(PendingPayouts::<Test>::get(1, STABLE).unwrap().amount, 360);
+		assert_ok!(Assets::thaw_asset(RuntimeOrigin::signed(pallet_account()), STABLE));
+		assert_ok!(Lottery::claim_winnings(RuntimeOrigin::signed(1), STABLE));
+		assert_eq!(Assets::balance(STABLE, 1), 990 + 360);
+	});
+}
+
+#[test]
+fn pulled_winnings_can_be_claimed() {
+	new_test_ext().execute_with(|| {
+		PullPayouts::set(true);
+		rig_spin(0, 17);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+
+		run_to_block(4);
+		assert_eq!(Balances::free_balance(1), 90);
+		assert_eq!(PendingPayouts::<Test>::get(1, NATIVE).unwrap().amount, 360);
+		assert_eq!(Lottery::house_balance(NATIVE), 10_010 - 50 - 1 - 360);
+
+		// Anybody can pay the winnings to their owner.
+		assert_ok!(Lottery::claim_for(RuntimeOrigin::signed(3), 1, NATIVE));
+		assert_eq!(Balances::free_balance(1), 90 + 360);
+		assert_noop!(
+			Lottery::claim_winnings(RuntimeOrigin::signed(1), NATIVE),
+			Error::<Test>::NothingToClaim
+		);
+	});
+}
+
+#[test]
+fn winnings_are_claimed_on_behalf_of_contracts() {
+	new_test_ext().execute_with(|| {
+		PullPayouts::set(true);
+		rig_spin(0, 17);
+		assert_ok!(Lottery::try_place_bet(1, NATIVE, 10, Bet::Full(17)));
+
+		run_to_block(4);
+		assert_eq!(Lottery::try_claim_winnings(&1, NATIVE), Ok(360));
+		assert_eq!(Balances::free_balance(1), 90 + 360);
+		assert_eq!(
+			Lottery::try_claim_winnings(&1, NATIVE),
+			Err(Error::<Test>::NothingToClaim.into())
+		);
+	});
+}
+
+#[test]
+fn unclaimed_winnings_expire() {
+	new_test_ext().execute_with(|| {
+		PullPayouts::set(true);
+		rig_spin(0, 17);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+
+		run_to_block(6);
+		assert!(PendingPayouts::<Test>::get(1, NATIVE).is_some());
+
+		run_to_block(7);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::PayoutExpired {
+			who: 1,
+			asset: NATIVE,
+			amount: 360,
+		}));
+		assert_noop!(
+			Lottery::claim_winnings(RuntimeOrigin::signed(1), NATIVE),
+			Error::<Test>::NothingToClaim
+		);
+	});
+}
+
+#[test]
+fn winnings_beyond_the_expiry_cap_expire_in_the_next_blocks() {
+	new_test_ext().execute_with(|| {
+		PullPayouts::set(true);
+		rig_spin(0, 17);
+		for who in 1..=3 {
+			assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(who), NATIVE, 10, Bet::Full(17)));
+		}
+		let pending =
+			|| (1..=3_u128).filter(|who| PendingPayouts::<Test>::get(who, NATIVE).is_some());
+
+		// Only so many winnings expire at once, and the rest are carried forward.
+		run_to_block(7);
+		assert_eq!(pending().count(), 1);
+
+		let who = pending().next().unwrap();
+		run_to_block(8);
+		assert_eq!(pending().count(), 0);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::PayoutExpired {
+			who,
+			asset: NATIVE,
+			amount: 360,
+		}));
+	});
+}
+
+fn code(code: &[u8]) -> ReferralCodeOf<Test> {
+	BoundedVec::truncate_from(code.to_vec())
+}
+
+#[test]
+fn players_bind_to_a_referrer_once() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::register_referrer(RuntimeOrigin::signed(2), code(b"two")));
+		assert_noop!(
+			Lottery::register_referrer(RuntimeOrigin::signed(3), code(b"two")),
+			Error::<Test>::ReferralCodeTaken
+		);
+		assert_noop!(
+			Lottery::bind_referrer(RuntimeOrigin::signed(1), code(b"three")),
+			Error::<Test>::UnknownReferralCode
+		);
+		assert_noop!(
+			Lottery::bind_referrer(RuntimeOrigin::signed(2), code(b"two")),
+			Error::<Test>::SelfReferral
+		);
+
+		assert_ok!(Lottery::bind_referrer(RuntimeOrigin::signed(1), code(b"two")));
+		assert_noop!(
+			Lottery::bind_referrer(RuntimeOrigin::signed(1), code(b"two")),
+			Error::<Test>::AlreadyReferred
+		);
+		assert_eq!(ReferrerInfos::<Test>::get(2).unwrap().players, 1);
+	});
+}
+
+#[test]
+fn referrers_share_the_house_income() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::register_referrer(RuntimeOrigin::signed(2), code(b"two")));
+		assert_ok!(Lottery::bind_referrer(RuntimeOrigin::signed(1), code(b"two")));
+
+		// The player loses 20 on black, but wins it back on the first dozen.
+		rig_spin(0, 1);
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			20,
+			Bet::Color(RouletteColor::Black)
+		));
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			10,
+			Bet::Dozen(traits::DozenOrColumn::First)
+		));
+		run_to_block(4);
+		assert_eq!(ReferralStatistics::<Test>::get(2, NATIVE), ReferralStats::default());
+
+		// The house wins 20 in the next round.
+		rig_spin(1, 1);
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			20,
+			Bet::Color(RouletteColor::Black)
+		));
+		run_to_block(7);
+		assert_eq!(
+			ReferralStatistics::<Test>::get(2, NATIVE),
+			ReferralStats { income: 20, rewards: 4 }
+		);
+
+		assert_ok!(Lottery::claim_referral_rewards(RuntimeOrigin::signed(2), NATIVE));
+		assert_eq!(Balances::free_balance(2), 104);
+		assert_noop!(
+			Lottery::claim_referral_rewards(RuntimeOrigin::signed(2), NATIVE),
+			Error::<Test>::NothingToClaim
+		);
+	});
+}
+
+#[test]
+fn vouchers_place_free_bets() {
+	new_test_ext().execute_with(|| {
+		let colors = BoundedVec::truncate_from(vec![BetKind::Color]);
+		assert_noop!(
+			Lottery::issue_voucher(RuntimeOrigin::signed(1), 1, 10, 3, colors.clone()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Lottery::issue_voucher(RuntimeOrigin::root(), 1, 10, 3, colors.clone()));
+		System::assert_last_event(RuntimeEvent::Lottery(Event::VoucherIssued {
+			voucher_id: 0,
+			who: 1,
+			amount: 10,
+			expires_at: 3,
+		}));
+
+		assert_noop!(
+			Lottery::place_bet_with_voucher(
+				RuntimeOrigin::signed(2),
+				0,
+				Bet::Color(RouletteColor::Red)
+			),
+			Error::<Test>::NotVoucherOwner
+		);
+		assert_noop!(
+			Lottery::place_bet_with_voucher(RuntimeOrigin::signed(1), 0, Bet::Full(1)),
+			Error::<Test>::BetKindNotAllowed
+		);
+
+		// Winning voucher bets are only paid their profit, which is all the house risks.
+		rig_spin(0, 1);
+		assert_ok!(Lottery::place_bet_with_voucher(
+			RuntimeOrigin::signed(1),
+			0,
+			Bet::Color(RouletteColor::Red)
+		));
+		System::assert_last_event(RuntimeEvent::Lottery(Event::VoucherRedeemed {
+			voucher_id: 0,
+			bet_id: 0,
+		}));
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(Exposure::<Test>::get(NATIVE, 0), 10);
+		assert_noop!(
+			Lottery::place_bet_with_voucher(
+				RuntimeOrigin::signed(1),
+				0,
+				Bet::Color(RouletteColor::Red)
+			),
+			Error::<Test>::UnknownVoucher
+		);
+		run_to_block(4);
+		assert_eq!(Balances::free_balance(1), 110);
+		assert_eq!(Balances::free_balance(pallet_account()), 9_990);
+
+		// Losing voucher bets cost nothing.
+		assert_ok!(Lottery::issue_voucher(RuntimeOrigin::root(), 1, 10, 6, BoundedVec::default()));
+		rig_spin(1, 1);
+		assert_ok!(Lottery::place_bet_with_voucher(RuntimeOrigin::signed(1), 1, Bet::Full(2)));
+		run_to_block(7);
+		assert_eq!(Balances::free_balance(1), 110);
+		assert_eq!(Balances::free_balance(pallet_account()), 9_990);
+
+		// Vouchers cannot be used after they expire, and are removed.
+		assert_ok!(Lottery::issue_voucher(RuntimeOrigin::root(), 1, 10, 8, colors));
+		run_to_block(9);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::VoucherExpired { voucher_id: 2 }));
+		assert!(!Vouchers::<Test>::contains_key(2));
+		assert_noop!(
+			Lottery::place_bet_with_voucher(
+				RuntimeOrigin::signed(1),
+				2,
+				Bet::Color(RouletteColor::Red)
+			),
+			Error::<Test>::UnknownVoucher
+		);
+	});
+}
+
+#[test]
+fn vouchers_need_a_stake_and_a_future_expiry() {
+	new_test_ext().execute_with(|| {
+		run_to_block(3);
+		assert_noop!(
+			Lottery::issue_voucher(RuntimeOrigin::root(), 1, 0, 4, BoundedVec::default()),
+			Error::<Test>::InvalidVoucher
+		);
+		assert_noop!(
+			Lottery::issue_voucher(RuntimeOrigin::root(), 1, 10, 3, BoundedVec::default()),
+			Error::<Test>::InvalidVoucher
+		);
+
+		// Only so many vouchers can expire in the same block.
+		assert_ok!(Lottery::issue_voucher(RuntimeOrigin::root(), 1, 10, 4, BoundedVec::default()));
+		assert_ok!(Lottery::issue_voucher(RuntimeOrigin::root(), 2, 10, 4, BoundedVec::default()));
+		assert_noop!(
+			Lottery::issue_voucher(RuntimeOrigin::root(), 3, 10, 4, BoundedVec::default()),
+			Error::<Test>::TooManyVoucherExpiries
+		);
+		assert_ok!(Lottery::issue_voucher(RuntimeOrigin::root(), 3, 10, 5, BoundedVec::default()));
+		assert_eq!(VoucherExpiries::<Test>::get(4).into_inner(), vec![0, 1]);
+
+		run_to_block(5);
+		assert!(!VoucherExpiries::<Test>::contains_key(4));
+		assert!(!Vouchers::<Test>::contains_key(0) && !Vouchers::<Test>::contains_key(1));
+		assert!(Vouchers::<Test>::contains_key(2));
+	});
+}
+
+#[test]
+fn genesis_sets_up_the_table() {
+	new_test_ext().execute_with(|| {
+		let red = Bet::Color(RouletteColor::Red);
+		GenesisConfig::<Test> {
+			paused: true,
+			bet_limits: vec![(STABLE, 5, 20)],
+			randomness_source: Some(beacon_source()),
+		}
+		.build();
+		assert_eq!(SourceOfRandomness::<Test>::get(), Some(beacon_source()));
+
+		assert_noop!(
+			Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 10, red.clone()),
+			Error::<Test>::Paused
+		);
+		assert_ok!(Lottery::set_paused(RuntimeOrigin::root(), false));
+		assert_noop!(
+			Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 4, red.clone()),
+			Error::<Test>::BetTooSmall
+		);
+		assert_noop!(
+			Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 21, red.clone()),
+			Error::<Test>::BetTooLarge
+		);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 20, red));
+	});
+}
+
+#[test]
+fn admin_calls_govern_the_lottery() {
+	new_test_ext().execute_with(|| {
+		let red = Bet::Color(RouletteColor::Red);
+		assert_noop!(Lottery::set_paused(RuntimeOrigin::signed(1), true), DispatchError::BadOrigin);
+		assert_noop!(
+			Lottery::set_bet_limits(RuntimeOrigin::signed(1), NATIVE, None),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Lottery::set_payout_multiple(RuntimeOrigin::signed(1), BetKind::Color, None),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Lottery::withdraw_house(RuntimeOrigin::signed(1), NATIVE, 1, 1),
+			DispatchError::BadOrigin
+		);
+
+		// Paused lotteries take no bets.
+		assert_ok!(Lottery::set_paused(RuntimeOrigin::root(), true));
+		System::assert_last_event(RuntimeEvent::Lottery(Event::PausedSet { paused: true }));
+		assert_noop!(
+			Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, red.clone()),
+			Error::<Test>::Paused
+		);
+		assert_ok!(Lottery::set_paused(RuntimeOrigin::root(), false));
+
+		// Stakes are kept within the limits of their asset.
+		assert_noop!(
+			Lottery::set_bet_limits(RuntimeOrigin::root(), NATIVE, Some((20, 5))),
+			Error::<Test>::InvalidBetLimits
+		);
+		assert_ok!(Lottery::set_bet_limits(RuntimeOrigin::root(), NATIVE, Some((5, 20))));
+		System::assert_last_event(RuntimeEvent::Lottery(Event::BetLimitsSet {
+			asset: NATIVE,
+			limits: Some((5, 20)),
+		}));
+		assert_noop!(
+			Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 4, red.clone()),
+			Error::<Test>::BetTooSmall
+		);
+		assert_noop!(
+			Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 21, red.clone()),
+			Error::<Test>::BetTooLarge
+		);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), STABLE, 21, red.clone()));
+
+		// The payout table overrides the multiples of the game.
+		assert_noop!(
+			Lottery::set_payout_multiple(RuntimeOrigin::root(), BetKind::Color, Some(0)),
+			Error::<Test>::InvalidPayoutMultiple
+		);
+		assert_ok!(Lottery::set_payout_multiple(RuntimeOrigin::root(), BetKind::Color, Some(3)));
+		System::assert_last_event(RuntimeEvent::Lottery(Event::PayoutMultipleSet {
+			kind: BetKind::Color,
+			multiple: Some(3),
+		}));
+		rig_spin(0, 1);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, red));
+		assert_eq!(Exposure::<Test>::get(NATIVE, 0), 30);
+		run_to_block(4);
+		assert_eq!(Balances::free_balance(1), 120);
+
+		// Only the house balance that does not back bets in play can be withdrawn.
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(2), NATIVE, 10, Bet::Full(2)));
+		let free = Lottery::house_balance(NATIVE) - Lottery::total_exposure(NATIVE);
+		assert_noop!(
+			Lottery::withdraw_house(RuntimeOrigin::root(), NATIVE, free + 1, 3),
+			Error::<Test>::NotEnoughFreeHouseBalance
+		);
+		assert_ok!(Lottery::withdraw_house(RuntimeOrigin::root(), NATIVE, free, 3));
+		System::assert_last_event(RuntimeEvent::Lottery(Event::HouseWithdrawn {
+			asset: NATIVE,
+			amount: free,
+			dest: 3,
+		}));
+		assert_eq!(Balances::free_balance(3), 100 + free);
+	});
+}
+
+#[test]
+fn bets_keep_the_payout_multiple_they_were_placed_with() {
+	new_test_ext().execute_with(|| {
+		let red = Bet::Color(RouletteColor::Red);
+		assert_ok!(Lottery::set_payout_multiple(RuntimeOrigin::root(), BetKind::Color, Some(3)));
+		rig_spin(0, 1);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, red.clone()));
+
+		// Changing the payout table only affects new bets, so the exposure stays right.
+		assert_ok!(Lottery::set_payout_multiple(RuntimeOrigin::root(), BetKind::Color, None));
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(2), NATIVE, 10, red));
+		assert_eq!(Exposure::<Test>::get(NATIVE, 0), 30 + 20);
+
+		run_to_block(4);
+		assert_eq!(Balances::free_balance(1), 90 + 30);
+		assert_eq!(Balances::free_balance(2), 90 + 20);
+	});
+}
+
+#[test]
+fn scheduled_bets_are_placed_every_round() {
+	new_test_ext().execute_with(|| {
+		let black = Bet::Color(RouletteColor::Black);
+		assert_noop!(
+			Lottery::schedule_bet(RuntimeOrigin::signed(1), NATIVE, black.clone(), 10, 0, 10),
+			Error::<Test>::InvalidSchedule
+		);
+		assert_noop!(
+			Lottery::schedule_bet(RuntimeOrigin::signed(1), NATIVE, black.clone(), 10, 2, 5),
+			Error::<Test>::InvalidSchedule
+		);
+
+		// The budget covers two bets, and the first one is placed right away.
+		rig_spin(0, 1);
+		assert_ok!(Lottery::schedule_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			black.clone(),
+			10,
+			3,
+			25
+		));
+		assert_eq!(Balances::free_balance(1), 90);
+		// The second player cannot afford a second bet.
+		assert_ok!(Lottery::schedule_bet(
+			RuntimeOrigin::signed(3),
+			NATIVE,
+			black.clone(),
+			60,
+			5,
+			300
+		));
+		assert_eq!(Balances::free_balance(3), 40);
+		assert_noop!(
+			Lottery::schedule_bet(RuntimeOrigin::signed(2), NATIVE, black.clone(), 10, 1, 10),
+			Error::<Test>::TooManyScheduledBets
+		);
+
+		run_to_block(2);
+		assert_eq!(Balances::free_balance(1), 80);
+		assert_eq!(Balances::free_balance(3), 40);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::ScheduledBetFailed {
+			schedule_id: 1,
+			error: Error::<Test>::NotEnoughBalance.into(),
+		}));
+		System::assert_has_event(RuntimeEvent::Lottery(Event::ScheduleEnded { schedule_id: 0 }));
+		assert_eq!(ScheduledBets::<Test>::count(), 0);
+
+		// Cancelled schedules are no longer placed.
+		rig_spin(1, 1);
+		assert_ok!(Lottery::schedule_bet(RuntimeOrigin::signed(2), NATIVE, black, 10, 5, 50));
+		assert_noop!(
+			Lottery::cancel_scheduled_bet(RuntimeOrigin::signed(1), 2),
+			Error::<Test>::NotScheduleOwner
+		);
+		assert_ok!(Lottery::cancel_scheduled_bet(RuntimeOrigin::signed(2), 2));
+		assert_noop!(
+			Lottery::cancel_scheduled_bet(RuntimeOrigin::signed(2), 2),
+			Error::<Test>::UnknownSchedule
+		);
+		run_to_block(5);
+		assert_eq!(Balances::free_balance(1), 80);
+		assert_eq!(Balances::free_balance(2), 90);
+	});
+}
+
+#[test]
+fn strategies_follow_their_progression() {
+	new_test_ext().execute_with(|| {
+		let black = Bet::Color(RouletteColor::Black);
+		assert_noop!(
+			Lottery::register_strategy(
+				RuntimeOrigin::signed(1),
+				NATIVE,
+				black.clone(),
+				10,
+				Progression::Martingale,
+				5,
+				5
+			),
+			Error::<Test>::InvalidStrategy
+		);
+
+		rig_spin(0, 1);
+		assert_ok!(Lottery::register_strategy(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			black.clone(),
+			5,
+			Progression::Martingale,
+			40,
+			5
+		));
+		assert_ok!(Lottery::register_strategy(
+			RuntimeOrigin::signed(2),
+			NATIVE,
+			black.clone(),
+			10,
+			Progression::DAlembert,
+			25,
+			100
+		));
+		assert_noop!(
+			Lottery::register_strategy(
+				RuntimeOrigin::signed(3),
+				NATIVE,
+				black.clone(),
+				10,
+				Progression::Fibonacci,
+				50,
+				50
+			),
+			Error::<Test>::TooManyStrategies
+		);
+
+		// Both lose. The second strategy cannot afford to lose 20 more.
+		run_to_block(4);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::StrategyStep {
+			strategy_id: 0,
+			bet_id: 2,
+			step: 1,
+			stake: 10,
+		}));
+		System::assert_has_event(RuntimeEvent::Lottery(Event::StrategyStopped {
+			strategy_id: 1,
+			reason: StopReason::StopLoss,
+			staked: 10,
+			paid: 0,
+		}));
+		assert_eq!(Balances::free_balance(1), 85);
+		assert_eq!(Balances::free_balance(2), 90);
+
+		// Cancelled strategies only have their bet in play played.
+		assert_ok!(Lottery::register_strategy(
+			RuntimeOrigin::signed(3),
+			NATIVE,
+			black,
+			10,
+			Progression::Fibonacci,
+			50,
+			50
+		));
+		assert_noop!(
+			Lottery::cancel_strategy(RuntimeOrigin::signed(1), 2),
+			Error::<Test>::NotStrategyOwner
+		);
+		assert_ok!(Lottery::cancel_strategy(RuntimeOrigin::signed(3), 2));
+
+		rig_spin(1, 1);
+		run_to_block(7);
+		assert_eq!(Balances::free_balance(1), 65);
+		assert_eq!(Balances::free_balance(3), 90);
+
+		// The win covers the losses and makes the take-profit.
+		rig_spin(2, 2);
+		run_to_block(10);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::StrategyStopped {
+			strategy_id: 0,
+			reason: StopReason::TakeProfit,
+			staked: 35,
+			paid: 40,
+		}));
+		assert_eq!(Balances::free_balance(1), 105);
+		assert_eq!(Strategies::<Test>::count(), 0);
+	});
+}
+
+#[test]
+fn settled_bets_are_summarized_on_chain_and_indexed_offchain() {
+	let mut ext = new_test_ext();
+	let summaries = ext.execute_with(|| {
+		rig_spin(0, 1);
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			10,
+			Bet::Color(RouletteColor::Red)
+		));
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(2)));
+		run_to_block(4);
+
+		let summaries = (Lottery::settled_bet(0).unwrap(), Lottery::settled_bet(1).unwrap());
+		assert_eq!((summaries.0.round, summaries.0.won, summaries.0.payout), (0, true, 20));
+		assert_eq!((summaries.1.round, summaries.1.won, summaries.1.payout), (0, false, 0));
+		summaries
+	});
+
+	ext.persist_offchain_overlay();
+	let db = ext.offchain_db();
+	let record = |bet_id: u64| {
+		let encoded = db.get(&bet_record_key(bet_id)).expect("settled bets are indexed");
+		BetRecordOf::<Test>::decode(&mut &encoded[..]).unwrap()
+	};
+
+	// Records can be checked against their summary on chain.
+	let red = record(0);
+	assert_eq!(red.bet.bet, Bet::Color(RouletteColor::Red));
+	assert_eq!((red.round, red.outcome, red.won, red.payout), (0, 1, true, 20));
+	assert_eq!(red.hash(), summaries.0.record_hash);
+	assert_eq!(record(1).hash(), summaries.1.record_hash);
+
+	// Bets are also indexed by account, in the order in which they were settled.
+	let mut bet_ids = (0..2)
+		.map(|index| {
+			let encoded = db.get(&account_bet_key(&1_u128, index)).unwrap();
+			u64::decode(&mut &encoded[..]).unwrap()
+		})
+		.collect::<Vec<_>>();
+	bet_ids.sort();
+	assert_eq!(bet_ids, vec![0, 1]);
+	assert!(db.get(&account_bet_key(&1_u128, 2)).is_none());
+}
+
+#[test]
+fn settled_bets_call_back_their_owner() {
+	new_test_ext().execute_with(|| {
+		rig_spin(0, 17);
+		assert_ok!(Lottery::try_place_bet_with_callback(
+			1,
+			NATIVE,
+			10,
+			Bet::Full(17),
+			[1, 2, 3, 4]
+		));
+		assert_ok!(Lottery::try_place_bet_with_callback(2, NATIVE, 10, Bet::Full(5), [5, 6, 7, 8]));
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(3), NATIVE, 10, Bet::Full(5)));
+
+		// Callbacks are paid for when the bet is placed, or the bet is not placed.
+		assert_eq!(Balances::free_balance(2), 89);
+		assert_noop!(
+			Lottery::try_place_bet_with_callback(4, NATIVE, 50, Bet::Full(5), [5, 6, 7, 8]),
+			Error::<Test>::NotEnoughBalance
+		);
+
+		run_to_block(4);
+		let mut callbacks = callbacks();
+		callbacks.sort_by_key(|(_, _, settlement)| settlement.bet_id);
+		assert_eq!(
+			callbacks,
+			vec![
+				(
+					1,
+					[1, 2, 3, 4],
+					BetSettlement { bet_id: 0, round: 0, outcome: 17, won: true, payout: 360 }
+				),
+				(
+					2,
+					[5, 6, 7, 8],
+					BetSettlement { bet_id: 1, round: 0, outcome: 17, won: false, payout: 0 }
+				),
+			]
+		);
+	});
+}
+
+#[test]
+fn callbacks_beyond_the_block_cap_wait_for_the_next_blocks() {
+	new_test_ext().execute_with(|| {
+		for who in 1..=3 {
+			assert_ok!(Lottery::try_place_bet_with_callback(
+				who,
+				NATIVE,
+				10,
+				Bet::Full(5),
+				[1, 2, 3, 4]
+			));
+		}
+
+		// The round settles at once, but only two of its callbacks are made in the block.
+		run_to_block(3);
+		frame_system::BlockHash::<Test>::insert(3, random_seed());
+		System::set_block_number(4);
+		let weight = Lottery::on_initialize(4);
+		assert!(Spins::<Test>::get(0).is_some());
+		assert_eq!(Lottery::total_exposure(NATIVE), 0);
+		assert_eq!(callbacks().len(), 2);
+		assert!(weight.ref_time() <= 2 * CallbackWeight::get().ref_time());
+
+		run_to_block(5);
+		assert_eq!(callbacks().len(), 3);
+		assert_eq!(callbacks()[2].0, 3);
+	});
+}
+
+/// Beacon publishing a round every 3 seconds since the unix epoch.
+fn beacon_source() -> RandomnessSourceOf<Test> {
+	RandomnessSource::Beacon(BeaconInfo {
+		public_key: beacon_pair().public().0,
+		genesis_time: 0,
+		period: 3,
+		url: BoundedVec::truncate_from(b"http://localhost:8090".to_vec()),
+	})
+}
+
+#[test]
+fn rounds_wait_for_the_beacon_round_they_are_bound_to() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::set_randomness_source(RuntimeOrigin::root(), beacon_source()));
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+
+		// Round 0 closes while beacon round 4 is the latest, so it is bound to round 6, which is
+		// published at least a beacon period later.
+		set_unix_time(10);
+		run_to_block(2);
+		System::assert_last_event(RuntimeEvent::Lottery(Event::RoundClosed {
+			round: 0,
+			ends_at: 1,
+		}));
+		assert_eq!(
+			Lottery::bound_randomness(&Lottery::spin_subject(0)),
+			Some(RandomnessRequest::Beacon(6))
+		);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(2), NATIVE, 10, Bet::Full(17)));
+
+		// Rounds that are already public are not accepted.
+		let randomness = H256::repeat_byte(9);
+		let signature = beacon_pair().sign(&beacon_message(5, &randomness));
+		assert_noop!(
+			Lottery::submit_beacon(RuntimeOrigin::none(), 5, randomness, signature),
+			Error::<Test>::UnexpectedBeaconRound
+		);
+
+		run_to_block(4);
+		assert!(Spins::<Test>::get(0).is_none());
+		assert_eq!(Lottery::total_exposure(NATIVE), 720);
+		assert_eq!(AwaitedBeaconRounds::<Test>::get(6).unwrap().games, 2);
+
+		let signature = beacon_pair().sign(&beacon_message(6, &randomness));
+		assert_ok!(Lottery::submit_beacon(RuntimeOrigin::none(), 6, randomness, signature));
+		run_to_block(5);
+		assert_eq!(
+			Spins::<Test>::get(0).unwrap().seed,
+			lottery_randomness(randomness, Lottery::spin_subject(0))
+		);
+		assert_eq!(
+			Spins::<Test>::get(1).unwrap().seed,
+			lottery_randomness(randomness, Lottery::spin_subject(1))
+		);
+		assert_eq!(Exposure::<Test>::get(NATIVE, 0), 0);
+		assert!(AwaitedBeaconRounds::<Test>::get(6).is_none());
+		assert!(Lottery::bound_randomness(&Lottery::spin_subject(0)).is_none());
+	});
+}
+
+#[test]
+fn rounds_waiting_for_randomness_are_played_over_several_blocks() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::set_randomness_source(RuntimeOrigin::root(), beacon_source()));
+		for block in 1..=3 {
+			run_to_block(block);
+			assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+		}
+		run_to_block(4);
+		assert_eq!(RoundIndex::<Test>::get(), 3);
+
+		// Once randomness comes in, only so many of the waiting rounds are played at once.
+		let randomness = H256::repeat_byte(9);
+		let signature = beacon_pair().sign(&beacon_message(3, &randomness));
+		assert_ok!(Lottery::submit_beacon(RuntimeOrigin::none(), 3, randomness, signature));
+		run_to_block(5);
+		assert!(Spins::<Test>::get(1).is_some());
+		assert!(Spins::<Test>::get(2).is_none());
+
+		run_to_block(6);
+		assert!(Spins::<Test>::get(2).is_some());
+	});
+}
+
+#[test]
+fn rounds_with_more_bets_than_the_block_cap_are_settled_over_several_blocks() {
+	new_test_ext().execute_with(|| {
+		rig_spin(0, 1);
+		for who in (1..=5).chain(1..=5) {
+			assert_ok!(Lottery::place_bet(
+				RuntimeOrigin::signed(who),
+				NATIVE,
+				5,
+				Bet::Color(RouletteColor::Red)
+			));
+		}
+		let free_balances = || (1..=5).map(Balances::free_balance).sum::<u64>();
+		assert_eq!(free_balances(), 450);
+
+		// Only so many bets are settled at once, and the round is reported once all of them are.
+		run_to_block(4);
+		assert_eq!(free_balances(), 530);
+		assert!(Exposure::<Test>::get(NATIVE, 0) > 0);
+		assert!(!System::events().into_iter().any(|record| matches!(
+			record.event,
+			RuntimeEvent::Lottery(Event::RoulettePlayed { .. })
+		)));
+
+		run_to_block(5);
+		assert_eq!(free_balances(), 550);
+		assert_eq!(Exposure::<Test>::get(NATIVE, 0), 0);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::RoulettePlayed {
+			round: 0,
+			asset: NATIVE,
+			seed: Spins::<Test>::get(0).unwrap().seed,
+			winner_number: 1,
+			players: 10,
+			income: 0,
+			payout: 100,
+		}));
+	});
+}
+
+#[test]
+fn revealed_entropy_is_mixed_into_the_seed() {
+	new_test_ext().execute_with(|| {
+		let entropy = H256::repeat_byte(7);
+		let commitment = H256(blake2_256(entropy.as_ref()));
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			10,
+			Bet::Color(RouletteColor::Red)
+		));
+		assert_ok!(Lottery::commit_entropy(RuntimeOrigin::signed(1), commitment));
+		assert_eq!(Balances::free_balance(1), 85);
+		assert_noop!(
+			Lottery::reveal_entropy(RuntimeOrigin::signed(1), 0, entropy),
+			Error::<Test>::RevealNotOpen
+		);
+
+		// The round waits for the reveal.
+		run_to_block(2);
+		assert!(Spins::<Test>::get(0).is_none());
+		assert_noop!(
+			Lottery::reveal_entropy(RuntimeOrigin::signed(1), 0, H256::zero()),
+			Error::<Test>::InvalidReveal
+		);
+		assert_ok!(Lottery::reveal_entropy(RuntimeOrigin::signed(1), 0, entropy));
+
+		run_to_block(5);
+		let spin = Spins::<Test>::get(0).unwrap();
+		let randomness = lottery_randomness(random_seed(), Lottery::spin_subject(0));
+		assert_eq!(spin.seed, traits::mix_entropy(randomness, &[entropy]));
+		assert_eq!(CommitmentDeposits::<Test>::get(), 0);
+	});
+}
+
+#[test]
+fn unrevealed_entropy_forfeits_deposit() {
+	new_test_ext().execute_with(|| {
+		let commitment = H256(blake2_256(H256::repeat_byte(7).as_ref()));
+		assert_ok!(Lottery::place_bet(
+			RuntimeOrigin::signed(1),
+			NATIVE,
+			10,
+			Bet::Color(RouletteColor::Red)
+		));
+
+		// Account 2 does not bet, so it has to be a designated revealer.
+		assert_noop!(
+			Lottery::commit_entropy(RuntimeOrigin::signed(2), commitment),
+			Error::<Test>::NotAllowedToCommit
+		);
+		assert_ok!(Lottery::set_revealer(RuntimeOrigin::root(), 2, true));
+		assert_ok!(Lottery::commit_entropy(RuntimeOrigin::signed(2), commitment));
+		assert_eq!(CommitmentDeposits::<Test>::get(), 5);
+
+		run_to_block(5);
+		assert!(Spins::<Test>::get(0).is_none());
+
+		run_to_block(6);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::EntropyForfeited {
+			round: 0,
+			who: 2,
+			deposit: 5,
+		}));
+		assert_eq!(
+			Spins::<Test>::get(0).unwrap().seed,
+			lottery_randomness(random_seed(), Lottery::spin_subject(0))
+		);
+		assert_eq!(Balances::free_balance(2), 95);
+		assert_eq!(CommitmentDeposits::<Test>::get(), 0);
+	});
+}
+
+#[test]
+fn rounds_with_commitments_are_bound_once_reveals_are_over() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::set_randomness_source(RuntimeOrigin::root(), beacon_source()));
+		let entropy = H256::repeat_byte(7);
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+		assert_ok!(Lottery::commit_entropy(
+			RuntimeOrigin::signed(1),
+			H256(blake2_256(entropy.as_ref()))
+		));
+
+		// The round is not bound to a beacon round while its commitment can be revealed.
+		run_to_block(3);
+		assert!(Lottery::bound_randomness(&Lottery::spin_subject(0)).is_none());
+
+		// Once the reveal period is over, the round is bound and reveals are closed.
+		run_to_block(4);
+		assert_eq!(
+			Lottery::bound_randomness(&Lottery::spin_subject(0)),
+			Some(RandomnessRequest::Beacon(3))
+		);
+		assert_noop!(
+			Lottery::reveal_entropy(RuntimeOrigin::signed(1), 0, entropy),
+			Error::<Test>::RevealNotOpen
+		);
+
+		let randomness = H256::repeat_byte(9);
+		let signature = beacon_pair().sign(&beacon_message(3, &randomness));
+		assert_ok!(Lottery::submit_beacon(RuntimeOrigin::none(), 3, randomness, signature));
+		run_to_block(5);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::EntropyForfeited {
+			round: 0,
+			who: 1,
+			deposit: 5,
+		}));
+		assert_eq!(
+			Spins::<Test>::get(0).unwrap().seed,
+			lottery_randomness(randomness, Lottery::spin_subject(0))
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_submits_beacon_round() {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	ext.register_extension(OffchainDbExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	let randomness = H256::repeat_byte(9);
+	let signature = beacon_pair().sign(&beacon_message(3, &randomness));
+	offchain_state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: "http://localhost:8090/public/3".into(),
+		response: Some(
+			format!(
+				r#"{{"round": 3, "randomness": "{}", "signature": "{}"}}"#,
+				HexDisplay::from(&randomness.as_ref()),
+				HexDisplay::from(&signature.0.as_ref()),
+			)
+			.into_bytes(),
+		),
+		sent: true,
+		..Default::default()
+	});
+
+	ext.execute_with(|| {
+		assert_ok!(Lottery::set_randomness_source(RuntimeOrigin::root(), beacon_source()));
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+		run_to_block(2);
+
+		// Round 3 of the beacon is not published yet.
+		offchain_state.write().timestamp = Timestamp::from_unix_millis(5_000);
+		Lottery::offchain_worker(2);
+		assert!(pool_state.read().transactions.is_empty());
+
+		offchain_state.write().timestamp = Timestamp::from_unix_millis(6_000);
+		Lottery::offchain_worker(2);
+		let tx = pool_state.write().transactions.pop().unwrap();
+		let tx = UncheckedExtrinsic::decode(&mut &*tx).unwrap();
+		assert!(tx.signature.is_none());
+		assert_eq!(
+			tx.function,
+			RuntimeCall::Lottery(crate::Call::submit_beacon { round: 3, randomness, signature })
+		);
+	});
+}
+
+#[test]
+fn only_awaited_beacon_rounds_are_accepted() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::set_randomness_source(RuntimeOrigin::root(), beacon_source()));
+		let randomness = H256::repeat_byte(9);
+		let signature = beacon_pair().sign(&beacon_message(3, &randomness));
+		let call =
+			crate::Call::submit_beacon { round: 3, randomness, signature: signature.clone() };
+		assert_noop!(
+			Lottery::submit_beacon(RuntimeOrigin::none(), 3, randomness, signature.clone()),
+			Error::<Test>::UnexpectedBeaconRound
+		);
+		assert_eq!(
+			Lottery::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into()
+		);
+
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+		run_to_block(2);
+		assert_noop!(
+			Lottery::submit_beacon(RuntimeOrigin::none(), 3, H256::zero(), signature.clone()),
+			Error::<Test>::InvalidBeaconSignature
+		);
+		assert!(Lottery::validate_unsigned(TransactionSource::External, &call).is_ok());
+
+		assert_ok!(Lottery::submit_beacon(RuntimeOrigin::none(), 3, randomness, signature.clone()));
+		System::assert_last_event(RuntimeEvent::Lottery(Event::BeaconRoundSubmitted {
+			round: 3,
+			randomness,
+		}));
+		assert_noop!(
+			Lottery::submit_beacon(RuntimeOrigin::none(), 3, randomness, signature),
+			Error::<Test>::UnexpectedBeaconRound
+		);
+	});
+}
+
+#[test]
+fn randomness_source_is_set_by_the_admin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Lottery::set_randomness_source(RuntimeOrigin::signed(1), beacon_source()),
+			DispatchError::BadOrigin
+		);
+		let source = RandomnessSource::Beacon(BeaconInfo {
+			public_key: beacon_pair().public().0,
+			genesis_time: 0,
+			period: 0,
+			url: BoundedVec::truncate_from(b"http://localhost:8090".to_vec()),
+		});
+		assert_noop!(
+			Lottery::set_randomness_source(RuntimeOrigin::root(), source),
+			Error::<Test>::InvalidRandomnessSource
+		);
+
+		assert_ok!(Lottery::set_randomness_source(RuntimeOrigin::root(), beacon_source()));
+		System::assert_last_event(RuntimeEvent::Lottery(Event::RandomnessSourceSet {
+			source: beacon_source(),
+		}));
+
+		// Closed rounds keep the beacon round they are bound to after the source changes.
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+		run_to_block(2);
+		assert_ok!(Lottery::set_randomness_source(
+			RuntimeOrigin::root(),
+			RandomnessSource::BlockHash
+		));
+		run_to_block(3);
+		assert!(Spins::<Test>::get(0).is_none());
+		assert_eq!(
+			Lottery::bound_randomness(&Lottery::spin_subject(0)),
+			Some(RandomnessRequest::Beacon(3))
+		);
+	});
+}
+
+fn ticket(numbers: Vec<u32>) -> TicketNumbersOf<Test> {
+	BoundedVec::truncate_from(numbers)
+}
+
+#[test]
+fn buy_ticket_rejects_invalid_numbers() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Lottery::buy_ticket(RuntimeOrigin::signed(1), ticket(vec![1, 2])),
+			Error::<Test>::InvalidTicket
+		);
+		assert_noop!(
+			Lottery::buy_ticket(RuntimeOrigin::signed(1), ticket(vec![1, 2, 11])),
+			Error::<Test>::OutOfRange
+		);
+		assert_noop!(
+			Lottery::buy_ticket(RuntimeOrigin::signed(1), ticket(vec![1, 2, 2])),
+			Error::<Test>::InvalidTicket
+		);
+	});
+}
+
+#[test]
+fn draw_pays_jackpot() {
+	new_test_ext().execute_with(|| {
+		let winning_numbers =
+			Lottery::draw_numbers(lottery_randomness(random_seed(), Lottery::draw_subject(0)));
+		let losing_numbers: Vec<u32> =
+			(1..=10).filter(|number| !winning_numbers.contains(number)).take(3).collect();
+
+		assert_ok!(Lottery::buy_ticket(RuntimeOrigin::signed(1), ticket(winning_numbers.clone())));
+		assert_ok!(Lottery::buy_ticket(RuntimeOrigin::signed(2), ticket(losing_numbers)));
+		assert_eq!(Pot::<Test>::get(), 20);
+		assert_eq!(Balances::free_balance(draw_account()), 70);
+
+		run_to_block(8);
+
+		// The jackpot takes half of the pot and the other half carries over.
+		System::assert_has_event(RuntimeEvent::Lottery(Event::PrizeAwarded {
+			draw: 0,
+			who: 1,
+			matches: 3,
+			amount: 10,
+		}));
+		System::assert_has_event(RuntimeEvent::Lottery(Event::DrawCompleted {
+			draw: 0,
+			numbers: ticket(winning_numbers),
+			tickets: 2,
+			pot: 20,
+			payout: 10,
+		}));
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(Pot::<Test>::get(), 10);
+		assert_eq!(DrawIndex::<Test>::get(), 1);
+	});
+}
+
+#[test]
+fn jackpot_rolls_over_without_winner() {
+	new_test_ext().execute_with(|| {
+		let winning_numbers =
+			Lottery::draw_numbers(lottery_randomness(random_seed(), Lottery::draw_subject(0)));
+		let losing_numbers: Vec<u32> =
+			(1..=10).filter(|number| !winning_numbers.contains(number)).take(3).collect();
+
+		assert_ok!(Lottery::buy_ticket(RuntimeOrigin::signed(2), ticket(losing_numbers)));
+
+		run_to_block(7);
+		assert_eq!(DrawIndex::<Test>::get(), 0);
+
+		run_to_block(8);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::JackpotRolledOver {
+			draw: 0,
+			amount: 10,
+		}));
+		assert_eq!(Pot::<Test>::get(), 10);
+		assert_eq!(DrawIndex::<Test>::get(), 1);
+	});
+}
+
+#[test]
+fn draws_are_bound_to_a_block_built_after_ticket_sales_close() {
+	new_test_ext().execute_with(|| {
+		// The first ticket, bought in block 1, sets the draw to close in block 6.
+		assert_ok!(Lottery::buy_ticket(RuntimeOrigin::signed(1), ticket(vec![1, 2, 3])));
+		run_to_block(5);
+		assert_ok!(Lottery::buy_ticket(RuntimeOrigin::signed(2), ticket(vec![4, 5, 6])));
+		assert!(Lottery::bound_randomness(&Lottery::draw_subject(0)).is_none());
+
+		// From block 6 on, tickets are no longer sold and the draw is bound to the hash of block 7,
+		// past the randomness delay.
+		run_to_block(6);
+		assert_noop!(
+			Lottery::buy_ticket(RuntimeOrigin::signed(3), ticket(vec![7, 8, 9])),
+			Error::<Test>::DrawClosed
+		);
+		assert_eq!(
+			Lottery::bound_randomness(&Lottery::draw_subject(0)),
+			Some(RandomnessRequest::BlockHash(7))
+		);
+
+		run_to_block(7);
+		assert_eq!(DrawIndex::<Test>::get(), 0);
+		run_to_block(8);
+		assert_eq!(DrawIndex::<Test>::get(), 1);
+		assert!(Lottery::bound_randomness(&Lottery::draw_subject(0)).is_none());
+	});
+}
+
+#[test]
+fn draws_wait_for_randomness_known_after_tickets_stop_selling() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::set_randomness_source(RuntimeOrigin::root(), beacon_source()));
+		let randomness = H256::repeat_byte(9);
+		let winning_numbers =
+			Lottery::draw_numbers(lottery_randomness(randomness, Lottery::draw_subject(0)));
+		let losing_numbers: Vec<u32> =
+			(1..=10).filter(|number| !winning_numbers.contains(number)).take(3).collect();
+		assert_ok!(Lottery::buy_ticket(RuntimeOrigin::signed(1), ticket(losing_numbers.clone())));
+
+		// The draw period is over, so the draw is bound to a beacon round and stops selling.
+		run_to_block(6);
+		assert_eq!(DrawIndex::<Test>::get(), 0);
+		assert_noop!(
+			Lottery::buy_ticket(RuntimeOrigin::signed(2), ticket(losing_numbers.clone())),
+			Error::<Test>::DrawClosed
+		);
+
+		let signature = beacon_pair().sign(&beacon_message(3, &randomness));
+		assert_ok!(Lottery::submit_beacon(RuntimeOrigin::none(), 3, randomness, signature));
+		run_to_block(7);
+		System::assert_has_event(RuntimeEvent::Lottery(Event::DrawCompleted {
+			draw: 0,
+			numbers: ticket(winning_numbers),
+			tickets: 1,
+			pot: 10,
+			payout: 0,
+		}));
+		assert!(AwaitedBeaconRounds::<Test>::get(3).is_none());
+		assert_ok!(Lottery::buy_ticket(RuntimeOrigin::signed(2), ticket(losing_numbers)));
+	});
+}
+
+#[test]
+fn migration_to_v1_refunds_bets_in_play_and_drops_settled_bets() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<Lottery>();
+		let old_bet =
+			|id, owner| OldBetData { id, owner, amount: 10, block: 1, bet: Bet::Full(17) };
+		v1::Bets::<Test>::insert(0, old_bet(0, 2));
+		v1::OngoingBets::<Test>::insert(1, old_bet(1, 1));
+		// Stakes in play and the worst case payout of the house were locked.
+		Balances::set_lock(*b"roulette", &1, 10, WithdrawReasons::RESERVE);
+		Balances::set_lock(*b"roulette", &pallet_account(), 360, WithdrawReasons::RESERVE);
+
+		#[cfg(feature = "try-runtime")]
+		let state = MigrateToV1::<Test, Balances>::pre_upgrade().unwrap();
+		MigrateToV1::<Test, Balances>::on_runtime_upgrade();
+		#[cfg(feature = "try-runtime")]
+		MigrateToV1::<Test, Balances>::post_upgrade(state).unwrap();
+
+		assert_eq!(StorageVersion::get::<Lottery>(), StorageVersion::new(1));
+		assert!(Balances::locks(1).is_empty());
+		assert!(Balances::locks(pallet_account()).is_empty());
+		assert_eq!(v1::OngoingBets::<Test>::iter_keys().count(), 0);
+		assert_eq!(v1::Bets::<Test>::iter_keys().count(), 0);
+
+		// Bets placed in the new layout are left alone once the storage is migrated.
+		assert_ok!(Lottery::place_bet(RuntimeOrigin::signed(1), NATIVE, 10, Bet::Full(17)));
+		MigrateToV1::<Test, Balances>::on_runtime_upgrade();
+		assert_eq!(Lottery::ongoing_bets_of(&1, 10).len(), 1);
+	});
+}
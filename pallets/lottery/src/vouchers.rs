@@ -0,0 +1,23 @@
+//! Expiry of free bet vouchers: vouchers that are not used in time are removed when the block
+//! after their expiry starts, at most `MaxVoucherExpiries` of them per block.
+
+use crate::pallet::{Config, Event, Pallet, VoucherExpiries, Vouchers};
+use frame_support::pallet_prelude::*;
+use sp_runtime::{traits::One, Saturating};
+
+impl<T: Config> Pallet<T> {
+	/// Removes the vouchers that expired in the block before `now`.
+	pub(crate) fn expire_vouchers(now: T::BlockNumber) -> Weight {
+		let expired = VoucherExpiries::<T>::take(now.saturating_sub(One::one()));
+		let mut weight = T::DbWeight::get().reads_writes(1, 1);
+
+		for voucher_id in expired {
+			// Vouchers used to place a bet are already gone.
+			if Vouchers::<T>::take(voucher_id).is_some() {
+				Self::deposit_event(Event::VoucherExpired { voucher_id });
+			}
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+		}
+		weight
+	}
+}
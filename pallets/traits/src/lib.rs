@@ -1,11 +1,22 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(test)]
+mod tests;
+
 use codec::{Decode, Encode};
-use frame_support::{inherent::Vec, pallet_prelude::MaxEncodedLen, RuntimeDebug};
+use frame_support::{
+	inherent::Vec,
+	pallet_prelude::{MaxEncodedLen, Parameter},
+	traits::ConstU32,
+	weights::Weight,
+	BoundedVec, RuntimeDebug,
+};
+use sp_core::{hashing::blake2_256, H256};
 
 #[derive(
 	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
 )]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub enum DozenOrColumn {
 	First,
 	Second,
@@ -15,6 +26,7 @@ pub enum DozenOrColumn {
 #[derive(
 	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
 )]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub enum Half {
 	First,
 	Second,
@@ -23,6 +35,7 @@ pub enum Half {
 #[derive(
 	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
 )]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub enum OddOrEven {
 	Odd,
 	Even,
@@ -31,6 +44,7 @@ pub enum OddOrEven {
 #[derive(
 	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
 )]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub enum Bet {
 	Color(RouletteColor),
 	Full(u32),
@@ -40,28 +54,70 @@ pub enum Bet {
 	OddOrEven(OddOrEven),
 }
 
+/// Kind of a roulette bet, regardless of what it covers.
 #[derive(
 	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
 )]
-pub struct BetData<AccountId, BlockNumber, Balance> {
+pub enum BetKind {
+	Color,
+	Full,
+	Dozen,
+	Column,
+	Half,
+	OddOrEven,
+}
+
+impl Bet {
+	/// Kind of the bet.
+	pub fn kind(&self) -> BetKind {
+		match self {
+			Bet::Color(_) => BetKind::Color,
+			Bet::Full(_) => BetKind::Full,
+			Bet::Dozen(_) => BetKind::Dozen,
+			Bet::Column(_) => BetKind::Column,
+			Bet::Half(_) => BetKind::Half,
+			Bet::OddOrEven(_) => BetKind::OddOrEven,
+		}
+	}
+
+	/// States if the bet can be played on a wheel with `pockets` pockets, numbered from zero.
+	pub fn validate(&self, pockets: u32) -> bool {
+		match self {
+			Bet::Full(number) => *number < pockets,
+			_ => true,
+		}
+	}
+}
+
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct BetData<AccountId, AssetId, BlockNumber, Balance, Pick = Bet> {
 	/// Bet id.
 	pub id: u64,
 	/// Owner of the bet.
 	pub owner: AccountId,
+	/// Asset in which the bet is placed.
+	pub asset: AssetId,
 	/// Bet amount.
 	pub amount: Balance,
 	/// Block in which bet occurs.
 	pub block: BlockNumber,
 	/// Type of bet.
-	pub bet: Bet,
+	pub bet: Pick,
+	/// Voucher used to place the bet, whose stake was not paid by the owner.
+	pub voucher: Option<u64>,
+	/// Payout multiple of the bet, fixed when it is placed.
+	pub multiple: u32,
 }
 
 #[derive(Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug)]
-pub struct RouletteResult<AccountId, BlockNumber, Balance> {
+pub struct RouletteResult<AccountId, AssetId, BlockNumber, Balance, Pick = Bet> {
 	/// Block in which game took place.
 	pub block: BlockNumber,
 	/// Bets that participated in roulette.
-	pub bets: Vec<BetData<AccountId, BlockNumber, Balance>>,
+	pub bets: Vec<BetData<AccountId, AssetId, BlockNumber, Balance, Pick>>,
 	/// Winner number.
 	pub winner_number: u32,
 	/// Amount received from losers.
@@ -73,6 +129,33 @@ pub struct RouletteResult<AccountId, BlockNumber, Balance> {
 #[derive(
 	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
 )]
+pub struct Ticket<AccountId, Numbers> {
+	/// Owner of the ticket.
+	pub owner: AccountId,
+	/// Numbers chosen by the owner.
+	pub numbers: Numbers,
+}
+
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct DrawResult<BlockNumber, Numbers, Balance> {
+	/// Block in which the draw took place.
+	pub block: BlockNumber,
+	/// Winning numbers.
+	pub numbers: Numbers,
+	/// Amount of tickets that took part in the draw.
+	pub tickets: u32,
+	/// Pot at the time of the draw, including rollovers from previous draws.
+	pub pot: Balance,
+	/// Amount paid to winners.
+	pub payout: Balance,
+}
+
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub enum RouletteColor {
 	Red,
 	Black,
@@ -150,3 +233,534 @@ impl RouletteNumber for u32 {
 		}
 	}
 }
+
+/// A game of chance in which players bet on picks and a single outcome is drawn at random.
+///
+/// Outcomes are numbered from `0` to `outcomes() - 1`.
+pub trait Game {
+	/// What players bet on.
+	type Pick: Parameter + MaxEncodedLen;
+
+	/// Kind of a pick, regardless of the outcomes it covers.
+	type Kind: Parameter + MaxEncodedLen;
+
+	/// Amount of possible outcomes.
+	fn outcomes() -> u32;
+
+	/// Samples an outcome from a random seed, uniformly distributed over all outcomes.
+	fn outcome_from_seed(seed: &[u8]) -> u32 {
+		sample_outcome(seed, Self::outcomes())
+	}
+
+	/// Kind of a pick.
+	fn kind(pick: &Self::Pick) -> Self::Kind;
+
+	/// States if a pick wins, given the outcome.
+	fn is_winner(pick: &Self::Pick, outcome: u32) -> bool;
+
+	/// Multiple of the stake paid to a winning pick, the stake included.
+	fn payout_multiple(pick: &Self::Pick) -> u32;
+
+	/// States if a pick can be played at all.
+	fn validate(pick: &Self::Pick) -> bool;
+
+	/// Outcome covered by a pick that covers only one of them, such as a straight-up bet.
+	fn single_outcome(_pick: &Self::Pick) -> Option<u32> {
+		None
+	}
+}
+
+/// Single-zero roulette, with pockets from 0 to 36.
+pub struct Roulette;
+
+impl Game for Roulette {
+	type Pick = Bet;
+	type Kind = BetKind;
+
+	fn outcomes() -> u32 {
+		37
+	}
+
+	fn kind(pick: &Bet) -> BetKind {
+		pick.kind()
+	}
+
+	fn is_winner(pick: &Bet, outcome: u32) -> bool {
+		match pick {
+			Bet::Color(color) => outcome.to_color().as_ref() == Some(color),
+			Bet::Full(number) => outcome == *number,
+			Bet::Dozen(dozen) => outcome.to_dozen().as_ref() == Some(dozen),
+			Bet::Column(column) => outcome.to_column().as_ref() == Some(column),
+			Bet::Half(half) => outcome.to_half().as_ref() == Some(half),
+			Bet::OddOrEven(odd_or_even) => match outcome.is_even() {
+				true => *odd_or_even == OddOrEven::Even,
+				false => *odd_or_even == OddOrEven::Odd,
+			},
+		}
+	}
+
+	fn payout_multiple(pick: &Bet) -> u32 {
+		match pick {
+			Bet::Color(_) => 2,
+			Bet::Full(_) => 36,
+			Bet::Dozen(_) => 3,
+			Bet::Column(_) => 3,
+			Bet::Half(_) => 2,
+			Bet::OddOrEven(_) => 2,
+		}
+	}
+
+	fn validate(pick: &Bet) -> bool {
+		pick.validate(Self::outcomes())
+	}
+
+	fn single_outcome(pick: &Bet) -> Option<u32> {
+		match pick {
+			Bet::Full(number) => Some(*number),
+			_ => None,
+		}
+	}
+}
+
+/// How the outcome of a spin is derived from its seed and subject.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub enum Derivation {
+	/// First four bytes of `blake2_256(seed ++ subject)` as a little endian `u32`, modulo the
+	/// amount of outcomes minus one. Used by early spins, it never draws the last outcome and
+	/// favours the lowest ones.
+	Blake2Concat,
+	/// `Game::outcome_from_seed` applied to `blake2_256(seed ++ subject)`, which samples the
+	/// outcome with `sample_outcome`.
+	Blake2Uniform,
+}
+
+/// Everything needed to recompute the outcome of a spin.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct SpinRecord<BlockNumber> {
+	/// Seed returned by the randomness source, mixed with the entropy revealed by players with
+	/// `mix_entropy` if there was any.
+	pub seed: H256,
+	/// Subject mixed with the seed, unique to the spin.
+	pub subject: BoundedVec<u8, ConstU32<32>>,
+	/// How the outcome is derived from the seed and the subject.
+	pub derivation: Derivation,
+	/// Block in which the spin took place.
+	pub block: BlockNumber,
+	/// Outcome of the spin.
+	pub outcome: u32,
+}
+
+/// Hash commitment to the entropy that a player contributes to a round.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct EntropyCommitment<Balance> {
+	/// `blake2_256` hash of the entropy.
+	pub commitment: H256,
+	/// Deposit forfeited if the entropy is not revealed in time.
+	pub deposit: Balance,
+	/// Entropy once revealed.
+	pub reveal: Option<H256>,
+}
+
+/// Drand-style randomness beacon: round `r` is published at `genesis_time + (r - 1) * period`,
+/// signed by `public_key`.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct BeaconInfo<Url> {
+	/// Ed25519 public key that signs the rounds.
+	pub public_key: [u8; 32],
+	/// Unix time, in seconds, at which the first round is published.
+	pub genesis_time: u64,
+	/// Seconds between rounds.
+	pub period: u64,
+	/// Base URL of the beacon, which serves every round under `/public/<round>`.
+	pub url: Url,
+}
+
+impl<Url> BeaconInfo<Url> {
+	/// Latest round published at unix time `now`, in seconds, or zero before the first one.
+	pub fn round_at(&self, now: u64) -> u64 {
+		match now.checked_sub(self.genesis_time) {
+			Some(elapsed) => elapsed / self.period.max(1) + 1,
+			None => 0,
+		}
+	}
+
+	/// Unix time, in seconds, at which `round` is published.
+	pub fn published_at(&self, round: u64) -> u64 {
+		self.genesis_time
+			.saturating_add(round.saturating_sub(1).saturating_mul(self.period))
+	}
+}
+
+/// Where the randomness of the games comes from.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum RandomnessSource<Url> {
+	/// Rounds of a randomness beacon, submitted by offchain workers.
+	Beacon(BeaconInfo<Url>),
+	/// Hash of a block built after a game stops taking bets. Block authors can bias it, so it
+	/// is only fit for development chains.
+	BlockHash,
+}
+
+/// Randomness that a game is bound to once it stops taking bets.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub enum RandomnessRequest<BlockNumber> {
+	/// A round of the randomness beacon.
+	Beacon(u64),
+	/// The hash of a block.
+	BlockHash(BlockNumber),
+}
+
+/// Beacon round that games are bound to.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct AwaitedBeacon<BlockNumber> {
+	/// Ed25519 public key that has to sign the round.
+	pub public_key: [u8; 32],
+	/// Unix time, in seconds, at which the beacon publishes the round.
+	pub published_at: u64,
+	/// Games bound to the round that have not been played yet.
+	pub games: u32,
+	/// Randomness of the round and the block in which it was submitted, once it is.
+	pub randomness: Option<(H256, BlockNumber)>,
+}
+
+/// Free bet issued to a player. A losing voucher bet costs the player nothing and a winning one
+/// is only paid its profit.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct Voucher<AccountId, Balance, BlockNumber, Kinds> {
+	/// Player that can use the voucher.
+	pub owner: AccountId,
+	/// Stake of the bet placed with the voucher.
+	pub amount: Balance,
+	/// Last block in which the voucher can be used.
+	pub expires_at: BlockNumber,
+	/// Kinds of bets that the voucher can be used for, any of them when empty.
+	pub bet_kinds: Kinds,
+}
+
+/// Bet that a player places in every round until it runs out of rounds or budget.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct ScheduledBet<AccountId, AssetId, Balance, Pick = Bet> {
+	/// Player that places the bets.
+	pub owner: AccountId,
+	/// Asset in which the bets are placed.
+	pub asset: AssetId,
+	/// Amount staked in every round.
+	pub amount: Balance,
+	/// What the player bets on.
+	pub bet: Pick,
+	/// Rounds left to bet on.
+	pub rounds: u32,
+	/// Amount left to stake.
+	pub budget: Balance,
+}
+
+/// Rule that sets the stake of every bet of a strategy, as a multiple of its base stake that
+/// depends on the step the strategy is at.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub enum Progression {
+	/// Doubles the stake after every loss and goes back to the base stake after a win.
+	Martingale,
+	/// Adds the base stake after every loss and takes it away after a win.
+	DAlembert,
+	/// Follows the Fibonacci sequence, a number forward after every loss and two back after a
+	/// win.
+	Fibonacci,
+}
+
+impl Progression {
+	/// Multiple of the base stake bet at `step`, starting from zero.
+	pub fn units(&self, step: u32) -> u32 {
+		match self {
+			Progression::Martingale => 2_u32.checked_pow(step).unwrap_or(u32::MAX),
+			Progression::DAlembert => step.saturating_add(1),
+			Progression::Fibonacci => {
+				let (mut current, mut next) = (1_u32, 1_u32);
+				for _ in 0..step {
+					(current, next) = (next, current.saturating_add(next));
+					if current == u32::MAX {
+						break
+					}
+				}
+				current
+			},
+		}
+	}
+
+	/// Step that follows `step` after a bet that won or lost.
+	pub fn next_step(&self, step: u32, won: bool) -> u32 {
+		match (self, won) {
+			(_, false) => step.saturating_add(1),
+			(Progression::Martingale, true) => 0,
+			(Progression::DAlembert, true) => step.saturating_sub(1),
+			(Progression::Fibonacci, true) => step.saturating_sub(2),
+		}
+	}
+}
+
+/// Why a betting strategy stopped.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub enum StopReason {
+	/// The next stake could take the losses of the strategy over its stop-loss.
+	StopLoss,
+	/// The profit of the strategy reached its take-profit.
+	TakeProfit,
+	/// The next bet could not be placed.
+	Failed,
+	/// The owner cancelled the strategy.
+	Cancelled,
+}
+
+/// Betting strategy that places a bet in every round, staking according to a progression.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct Strategy<AccountId, AssetId, Balance, Pick = Bet> {
+	/// Player that places the bets.
+	pub owner: AccountId,
+	/// Asset in which the bets are placed.
+	pub asset: AssetId,
+	/// What the player bets on.
+	pub bet: Pick,
+	/// Stake of the first step of the progression.
+	pub base_stake: Balance,
+	/// How stakes change from a bet to the next one.
+	pub progression: Progression,
+	/// Most that the strategy may lose.
+	pub stop_loss: Balance,
+	/// Profit at which the strategy stops.
+	pub take_profit: Balance,
+	/// Step of the progression of the bet in play.
+	pub step: u32,
+	/// Total staked by the strategy.
+	pub staked: Balance,
+	/// Total paid to the strategy.
+	pub paid: Balance,
+}
+
+/// Result of a bet, handed to the account that placed it when it asked to be called back.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct BetSettlement<Balance> {
+	/// Bet id.
+	pub bet_id: u64,
+	/// Round that played the bet.
+	pub round: u32,
+	/// Outcome of the spin.
+	pub outcome: u32,
+	/// Whether the bet won.
+	pub won: bool,
+	/// Amount paid to the bet.
+	pub payout: Balance,
+}
+
+/// What is kept on chain of a settled bet. Its full `BetRecord` is written to the offchain index
+/// of the nodes that enable it, and can be checked against `record_hash`.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct SettledBet<Balance> {
+	/// Round that played the bet.
+	pub round: u32,
+	/// Whether the bet won.
+	pub won: bool,
+	/// Amount paid or credited to the bet.
+	pub payout: Balance,
+	/// Hash of the full record of the bet.
+	pub record_hash: H256,
+}
+
+/// Full record of a settled bet.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct BetRecord<AccountId, AssetId, BlockNumber, Balance, Pick = Bet> {
+	/// The bet as it was placed.
+	pub bet: BetData<AccountId, AssetId, BlockNumber, Balance, Pick>,
+	/// Round that played the bet.
+	pub round: u32,
+	/// Outcome of the spin.
+	pub outcome: u32,
+	/// Whether the bet won.
+	pub won: bool,
+	/// Amount paid or credited to the bet.
+	pub payout: Balance,
+	/// Block in which the bet was settled.
+	pub settled_at: BlockNumber,
+}
+
+impl<AccountId, AssetId, BlockNumber, Balance, Pick>
+	BetRecord<AccountId, AssetId, BlockNumber, Balance, Pick>
+where
+	Self: Encode,
+{
+	/// Hash of the record that is kept on chain, `blake2_256` of its encoding.
+	pub fn hash(&self) -> H256 {
+		H256(blake2_256(&self.encode()))
+	}
+}
+
+/// Calls back the accounts, such as contracts, whose bets asked for it when they are settled.
+pub trait BetCallback<AccountId, Balance> {
+	/// Calls `selector` of `who` with the settlement of its bet, spending at most
+	/// `weight_limit`. Returns the weight spent.
+	fn on_settled(
+		who: &AccountId,
+		selector: [u8; 4],
+		settlement: BetSettlement<Balance>,
+		weight_limit: Weight,
+	) -> Weight;
+}
+
+impl<AccountId, Balance> BetCallback<AccountId, Balance> for () {
+	fn on_settled(
+		_who: &AccountId,
+		_selector: [u8; 4],
+		_settlement: BetSettlement<Balance>,
+		_weight_limit: Weight,
+	) -> Weight {
+		Weight::zero()
+	}
+}
+
+/// Winnings credited to a player, waiting to be claimed.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct PendingPayout<Balance, BlockNumber> {
+	/// Amount that can be claimed.
+	pub amount: Balance,
+	/// Block at which the winnings go back to the house if not claimed.
+	pub expires_at: BlockNumber,
+}
+
+/// What the bets of a round settled so far have yielded, in a single asset.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+	Default,
+)]
+pub struct RoundTotals<Balance> {
+	/// Bets settled.
+	pub players: u32,
+	/// Stakes of the losing bets, kept by the house.
+	pub income: Balance,
+	/// Payouts of the winning bets.
+	pub payout: Balance,
+}
+
+/// Referral code of a referrer and how many players it brought in.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+)]
+pub struct ReferrerInfo<Code> {
+	/// Code that players use to bind to the referrer.
+	pub code: Code,
+	/// Players bound to the referrer.
+	pub players: u32,
+}
+
+/// What the players brought in by a referrer have yielded, in a single asset.
+#[derive(
+	Encode, Decode, Eq, PartialEq, scale_info::TypeInfo, MaxEncodedLen, RuntimeDebug, Clone,
+	Default,
+)]
+pub struct ReferralStats<Balance> {
+	/// Net income of the house from the referred players, adding up rounds in which it was
+	/// positive.
+	pub income: Balance,
+	/// Rewards accrued to the referrer.
+	pub rewards: Balance,
+}
+
+/// Mixes the seed returned by the randomness source with the entropy revealed by players. Reveals
+/// are sorted first, so the order in which they were submitted does not matter.
+pub fn mix_entropy(seed: H256, reveals: &[H256]) -> H256 {
+	if reveals.is_empty() {
+		return seed
+	}
+	let mut reveals = reveals.to_vec();
+	reveals.sort();
+	H256(blake2_256(&(seed, reveals).encode()))
+}
+
+/// Samples an outcome uniformly distributed in `0..outcomes` from some entropy.
+///
+/// The entropy is read as little endian `u32` chunks of four bytes. Chunks at or above the
+/// largest multiple of `outcomes` that fits in a `u32` are rejected, since they would favour the
+/// lowest outcomes, and the first accepted one is reduced modulo `outcomes`. When every chunk is
+/// rejected, the entropy is hashed with `blake2_256` and sampling starts over on the hash.
+pub fn sample_outcome(entropy: &[u8], outcomes: u32) -> u32 {
+	if outcomes <= 1 {
+		return 0
+	}
+
+	let range = 1_u64 << 32;
+	let limit = range - range % u64::from(outcomes);
+	let mut candidates = entropy.to_vec();
+	loop {
+		for chunk in candidates.chunks_exact(4) {
+			let value = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+			if u64::from(value) < limit {
+				return value % outcomes
+			}
+		}
+		candidates = blake2_256(&candidates).to_vec();
+	}
+}
+
+/// Mixes the raw seed of a spin with its subject, as done by every `Derivation`.
+pub fn spin_entropy(seed: &[u8], subject: &[u8]) -> [u8; 32] {
+	let mut input = Vec::with_capacity(seed.len() + subject.len());
+	input.extend_from_slice(seed);
+	input.extend_from_slice(subject);
+	blake2_256(&input)
+}
+
+/// Recomputes the outcome of a spin of `G` from its recorded derivation, seed and subject.
+pub fn derive_outcome<G: Game>(derivation: &Derivation, seed: &[u8], subject: &[u8]) -> u32 {
+	let entropy = spin_entropy(seed, subject);
+	match derivation {
+		Derivation::Blake2Concat => {
+			let random_number = <u32>::decode(&mut &entropy[..])
+				.expect("secure hashes should always be bigger than u32; qed");
+			random_number % G::outcomes().saturating_sub(1).max(1)
+		},
+		Derivation::Blake2Uniform => G::outcome_from_seed(&entropy),
+	}
+}
+
+/// Recomputes the outcome of a spin of `G` from its recorded seed and subject, for spins using
+/// `Derivation::Blake2Uniform`.
+pub fn verify_outcome<G: Game>(seed: &[u8], subject: &[u8]) -> u32 {
+	derive_outcome::<G>(&Derivation::Blake2Uniform, seed, subject)
+}
+
+/// Recomputes the pocket of a roulette spin from its recorded seed and subject.
+pub fn verify_spin(seed: &[u8], subject: &[u8]) -> u32 {
+	verify_outcome::<Roulette>(seed, subject)
+}
@@ -0,0 +1,102 @@
+use crate::{
+	derive_outcome, sample_outcome, spin_entropy, Bet, Derivation, Game, Progression, Roulette,
+	RouletteColor,
+};
+use codec::Encode;
+use sp_core::hashing::blake2_256;
+
+/// Counts how many times every outcome is sampled from `samples` different seeds.
+fn histogram(outcomes: u32, samples: u32) -> Vec<u32> {
+	let mut counts = vec![0_u32; outcomes as usize];
+	for nonce in 0..samples {
+		let entropy = blake2_256(&nonce.encode());
+		counts[sample_outcome(&entropy, outcomes) as usize] += 1;
+	}
+	counts
+}
+
+/// Pearson's chi-squared statistic of a histogram against the uniform distribution.
+fn chi_squared(counts: &[u32]) -> f64 {
+	let total: u32 = counts.iter().sum();
+	let expected = f64::from(total) / counts.len() as f64;
+	counts
+		.iter()
+		.map(|count| (f64::from(*count) - expected).powi(2) / expected)
+		.sum()
+}
+
+#[test]
+fn samples_are_uniformly_distributed() {
+	for outcomes in [2_u32, 6, 37, 100] {
+		let counts = histogram(outcomes, 1_000 * outcomes);
+
+		// Five standard deviations above the mean of the chi-squared distribution.
+		let degrees = f64::from(outcomes - 1);
+		let threshold = degrees + 5.0 * (2.0 * degrees).sqrt();
+		let statistic = chi_squared(&counts);
+		assert!(statistic < threshold, "{} outcomes: {} >= {}", outcomes, statistic, threshold);
+	}
+}
+
+#[test]
+fn every_pocket_can_be_hit() {
+	let counts = histogram(Roulette::outcomes(), 37_000);
+	assert!(counts.iter().all(|count| *count > 0));
+	assert!(counts[36] > 0);
+}
+
+#[test]
+fn biased_values_are_rejected() {
+	// 4_294_967_295 is above the largest multiple of 37 that fits in a u32, so it is skipped.
+	let mut entropy = u32::MAX.to_le_bytes().to_vec();
+	entropy.extend_from_slice(&40_u32.to_le_bytes());
+	assert_eq!(sample_outcome(&entropy, 37), 3);
+
+	// When every chunk is rejected, the hash of the entropy is sampled instead.
+	let entropy = [u8::MAX; 32];
+	assert_eq!(sample_outcome(&entropy, 37), sample_outcome(&blake2_256(&entropy), 37));
+}
+
+#[test]
+fn degenerate_games_have_a_single_outcome() {
+	assert_eq!(sample_outcome(&[1, 2, 3, 4], 0), 0);
+	assert_eq!(sample_outcome(&[1, 2, 3, 4], 1), 0);
+}
+
+#[test]
+fn legacy_spins_can_still_be_verified() {
+	let (seed, subject) = ([7_u8; 32], (b"roulette", 0_u32).encode());
+	let entropy = spin_entropy(&seed, &subject);
+	let legacy = u32::from_le_bytes([entropy[0], entropy[1], entropy[2], entropy[3]]) % 36;
+
+	assert_eq!(derive_outcome::<Roulette>(&Derivation::Blake2Concat, &seed, &subject), legacy);
+	assert_eq!(
+		derive_outcome::<Roulette>(&Derivation::Blake2Uniform, &seed, &subject),
+		sample_outcome(&entropy, 37)
+	);
+}
+
+#[test]
+fn bets_are_validated_against_the_wheel() {
+	assert!(Bet::Full(36).validate(37));
+	assert!(!Bet::Full(37).validate(37));
+	assert!(!Roulette::validate(&Bet::Full(1000)));
+	assert!(Roulette::validate(&Bet::Color(RouletteColor::Red)));
+}
+
+#[test]
+fn progressions_follow_their_sequences() {
+	let units =
+		|progression: Progression| (0..7).map(|step| progression.units(step)).collect::<Vec<_>>();
+	assert_eq!(units(Progression::Martingale), vec![1, 2, 4, 8, 16, 32, 64]);
+	assert_eq!(units(Progression::DAlembert), vec![1, 2, 3, 4, 5, 6, 7]);
+	assert_eq!(units(Progression::Fibonacci), vec![1, 1, 2, 3, 5, 8, 13]);
+	assert_eq!(Progression::Martingale.units(40), u32::MAX);
+	assert_eq!(Progression::Fibonacci.units(100), u32::MAX);
+
+	assert_eq!(Progression::Martingale.next_step(3, true), 0);
+	assert_eq!(Progression::DAlembert.next_step(3, true), 2);
+	assert_eq!(Progression::Fibonacci.next_step(3, true), 1);
+	assert_eq!(Progression::Fibonacci.next_step(1, true), 0);
+	assert_eq!(Progression::Fibonacci.next_step(3, false), 4);
+}
@@ -0,0 +1,46 @@
+;; Calls the chain extension of the runtime. The input of the contract is the id of the function
+;; to call, as a little endian u32, followed by the input of the function. The contract returns the
+;; status code of the function, as a little endian u32, followed by its output.
+(module
+	(import "seal0" "seal_call_chain_extension"
+		(func $seal_call_chain_extension (param i32 i32 i32 i32 i32) (result i32))
+	)
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 16 16))
+
+	;; [0, 4) length of the input buffer
+	(data (i32.const 0) "\00\40")
+
+	;; [4, 16388) input buffer: function id followed by its input
+
+	;; [16388, 16392) length of the output buffer
+	(data (i32.const 16388) "\00\40")
+
+	;; [16392, 16396) status code of the function
+
+	;; [16396, 32780) output buffer
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+
+		(i32.store
+			(i32.const 16392)
+			(call $seal_call_chain_extension
+				(i32.load (i32.const 4))
+				(i32.const 8)
+				(i32.sub (i32.load (i32.const 0)) (i32.const 4))
+				(i32.const 16396)
+				(i32.const 16388)
+			)
+		)
+
+		(call $seal_return
+			(i32.const 0)
+			(i32.const 16392)
+			(i32.add (i32.load (i32.const 16388)) (i32.const 4))
+		)
+	)
+)
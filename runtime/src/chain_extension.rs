@@ -0,0 +1,193 @@
+//! Chain extension that lets ink! contracts play the roulette: contracts place bets on their own
+//! behalf, list the bets they have waiting to be played and read the results of past bets and
+//! spins. Contracts can also ask to be called back when their bets are settled, and claim the
+//! winnings credited to them when the runtime does not push payouts.
+//!
+//! Every function writes its SCALE encoded output to the contract buffer and returns one of the
+//! status codes below, which contracts map to their error type.
+
+use crate::{AccountId, AssetId, Balance, Contracts, Lottery, Runtime};
+use codec::Encode;
+use frame_support::{traits::Get, weights::Weight};
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig,
+};
+use pallet_lottery::Error;
+use sp_core::crypto::UncheckedFrom;
+use sp_runtime::DispatchError;
+use sp_std::vec::Vec;
+use traits::{Bet, BetCallback, BetSettlement};
+
+#[cfg(test)]
+mod tests;
+
+/// Places a bet of the contract. Takes `(AssetId, Balance, Bet)` and returns the bet id.
+pub const PLACE_BET: u16 = 1;
+/// Lists the bets of the contract waiting to be played, up to `ONGOING_BETS_LIMIT` of them.
+/// Returns `Vec<(u32, BetData)>`.
+pub const ONGOING_BETS: u16 = 2;
+/// Reads the summary of a bet that was played. Takes the bet id and returns `Option<SettledBet>`.
+pub const SETTLED_BET: u16 = 3;
+/// Reads the spin that played a round. Takes the round and returns `Option<SpinRecord>`.
+pub const SPIN: u16 = 4;
+/// Places a bet of the contract and calls the given selector of the contract back with its
+/// `BetSettlement`, for a `CallbackFee` in the native currency. Takes
+/// `(AssetId, Balance, Bet, [u8; 4])` and returns the bet id.
+pub const PLACE_BET_WITH_CALLBACK: u16 = 5;
+/// Pays the winnings credited to the contract. Takes the `AssetId` and returns the `Balance`
+/// paid.
+pub const CLAIM_WINNINGS: u16 = 6;
+
+/// The call succeeded.
+pub const SUCCESS: u32 = 0;
+/// The contract cannot afford the bet.
+pub const NOT_ENOUGH_BALANCE: u32 = 1;
+/// The house cannot cover the bet.
+pub const NOT_ENOUGH_HOUSE_BALANCE: u32 = 2;
+/// The bet cannot be played.
+pub const INVALID_BET: u32 = 3;
+/// The bet failed for any other reason.
+pub const BET_FAILED: u32 = 4;
+/// No winnings are credited to the contract.
+pub const NOTHING_TO_CLAIM: u32 = 5;
+
+/// Reads and writes that placing a bet costs besides reading the bets in play, like a scheduled
+/// bet.
+const PLACE_BET_READS_WRITES: (u64, u64) = (11, 7);
+/// Reads and writes that asking to be called back adds to placing a bet: paying the callback fee
+/// and registering the callback.
+const CALLBACK_READS_WRITES: (u64, u64) = (1, 3);
+/// Reads and writes of claiming winnings: the credited winnings, the transfer and the pending
+/// total.
+const CLAIM_READS_WRITES: (u64, u64) = (3, 4);
+/// Bets of a contract that are looked at when listing them.
+pub const ONGOING_BETS_LIMIT: u32 = 256;
+
+/// Roulette functions for contracts.
+#[derive(Default)]
+pub struct RouletteExtension;
+
+impl ChainExtension<Runtime> for RouletteExtension {
+	fn call<E: Ext<T = Runtime>>(
+		&mut self,
+		env: Environment<E, InitState>,
+	) -> Result<RetVal, DispatchError>
+	where
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		let func_id = env.func_id();
+		let mut env = env.buf_in_buf_out();
+		let db_weight = <Runtime as frame_system::Config>::DbWeight::get();
+
+		match func_id {
+			PLACE_BET | PLACE_BET_WITH_CALLBACK => {
+				let (asset, amount, bet, selector) = match func_id {
+					PLACE_BET => {
+						let (asset, amount, bet): (AssetId, Balance, Bet) = env.read_as()?;
+						(asset, amount, bet, None)
+					},
+					_ => {
+						let (asset, amount, bet, selector): (AssetId, Balance, Bet, [u8; 4]) =
+							env.read_as()?;
+						(asset, amount, bet, Some(selector))
+					},
+				};
+				// Placing a bet reads every bet of its round, so it is charged for them first.
+				let (mut reads, mut writes) = PLACE_BET_READS_WRITES;
+				reads = reads.saturating_add(Lottery::bets_in_play_reads());
+				if selector.is_some() {
+					let (callback_reads, callback_writes) = CALLBACK_READS_WRITES;
+					reads = reads.saturating_add(callback_reads);
+					writes = writes.saturating_add(callback_writes);
+				}
+				env.charge_weight(db_weight.reads_writes(reads, writes))?;
+
+				let contract: AccountId = env.ext().address().clone();
+				let placed = match selector {
+					Some(selector) =>
+						Lottery::try_place_bet_with_callback(contract, asset, amount, bet, selector),
+					None => Lottery::try_place_bet(contract, asset, amount, bet),
+				};
+				match placed {
+					Ok(bet_id) => {
+						env.write(&bet_id.encode(), false, None)?;
+						Ok(RetVal::Converging(SUCCESS))
+					},
+					Err(error) => Ok(RetVal::Converging(status_code(error))),
+				}
+			},
+			ONGOING_BETS => {
+				// Every bet looked at reads its round and the bet itself, and the listing stops
+				// one read past the last bet of the contract. Bets that are not there are refunded.
+				let charged =
+					env.charge_weight(db_weight.reads(2 * u64::from(ONGOING_BETS_LIMIT) + 1))?;
+				let contract: AccountId = env.ext().address().clone();
+				let bets = Lottery::ongoing_bets_of(&contract, ONGOING_BETS_LIMIT);
+				env.adjust_weight(charged, db_weight.reads(2 * bets.len() as u64 + 1));
+				env.write(&bets.encode(), false, None)?;
+				Ok(RetVal::Converging(SUCCESS))
+			},
+			SETTLED_BET => {
+				let bet_id: u64 = env.read_as()?;
+				env.charge_weight(db_weight.reads(1))?;
+				env.write(&Lottery::settled_bet(bet_id).encode(), false, None)?;
+				Ok(RetVal::Converging(SUCCESS))
+			},
+			SPIN => {
+				let round: u32 = env.read_as()?;
+				env.charge_weight(db_weight.reads(1))?;
+				env.write(&Lottery::spin_of(round).encode(), false, None)?;
+				Ok(RetVal::Converging(SUCCESS))
+			},
+			CLAIM_WINNINGS => {
+				let asset: AssetId = env.read_as()?;
+				let (reads, writes) = CLAIM_READS_WRITES;
+				env.charge_weight(db_weight.reads_writes(reads, writes))?;
+
+				let contract: AccountId = env.ext().address().clone();
+				match Lottery::try_claim_winnings(&contract, asset) {
+					Ok(amount) => {
+						env.write(&amount.encode(), false, None)?;
+						Ok(RetVal::Converging(SUCCESS))
+					},
+					Err(error) => Ok(RetVal::Converging(status_code(error))),
+				}
+			},
+			_ => Err(DispatchError::Other("Unknown roulette chain extension function")),
+		}
+	}
+}
+
+/// Status code that tells a contract why its bet or claim failed.
+fn status_code(error: DispatchError) -> u32 {
+	if error == DispatchError::from(Error::<Runtime>::NotEnoughBalance) {
+		NOT_ENOUGH_BALANCE
+	} else if error == DispatchError::from(Error::<Runtime>::NotEnoughBalanceInPalletAccount) {
+		NOT_ENOUGH_HOUSE_BALANCE
+	} else if error == DispatchError::from(Error::<Runtime>::OutOfRange) {
+		INVALID_BET
+	} else if error == DispatchError::from(Error::<Runtime>::NothingToClaim) {
+		NOTHING_TO_CLAIM
+	} else {
+		BET_FAILED
+	}
+}
+
+/// Calls the contracts that placed bets back with their settlement.
+pub struct ContractCallback;
+
+impl BetCallback<AccountId, Balance> for ContractCallback {
+	fn on_settled(
+		who: &AccountId,
+		selector: [u8; 4],
+		settlement: BetSettlement<Balance>,
+		weight_limit: Weight,
+	) -> Weight {
+		let mut input: Vec<u8> = selector.to_vec();
+		settlement.encode_to(&mut input);
+
+		// Contracts call themselves, so whatever they store is paid from their own balance.
+		Contracts::bare_call(who.clone(), who.clone(), 0, weight_limit, None, input, false)
+			.gas_consumed
+	}
+}
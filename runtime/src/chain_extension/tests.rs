@@ -0,0 +1,373 @@
+//! Tests of the roulette chain extension, through a contract that forwards its input to the
+//! extension and returns the status code and the output of the called function, and through the
+//! syndicate contract of `contracts/syndicate`.
+//!
+//! The syndicate tests deploy the contract as built by `cargo contract build`, so they are ignored
+//! unless asked for: build the contract, then run them with `cargo test -- --ignored`.
+
+use crate::{
+	chain_extension::{
+		CLAIM_WINNINGS, INVALID_BET, NOTHING_TO_CLAIM, ONGOING_BETS, ONGOING_BETS_LIMIT, PLACE_BET,
+		SUCCESS,
+	},
+	AccountId, Balance, Balances, BalancesConfig, BettingPeriod, BlockNumber, Contracts, Lottery,
+	LotteryConfig, NativeAssetId, RandomnessDelay, RoulettePalletId, Runtime, RuntimeOrigin,
+	System, DOLLARS,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	sp_io,
+	traits::{GenesisBuild, Get, OnInitialize},
+	weights::Weight,
+};
+use pallet_contracts_primitives::Code;
+use pallet_lottery::BetDataOf;
+use sp_core::{hashing::blake2_256, H256};
+use sp_runtime::traits::AccountIdConversion;
+use traits::{Bet, RandomnessSource, RouletteColor};
+
+const GAS_LIMIT: Weight = Weight::from_ref_time(100_000_000_000);
+
+fn alice() -> AccountId {
+	AccountId::new([1; 32])
+}
+
+fn bob() -> AccountId {
+	AccountId::new([2; 32])
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	BalancesConfig {
+		balances: vec![
+			(alice(), 1_000_000 * DOLLARS),
+			(bob(), 1_000_000 * DOLLARS),
+			(RoulettePalletId::get().into_account_truncating(), 1_000_000 * DOLLARS),
+		],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+	LotteryConfig { randomness_source: Some(RandomnessSource::BlockHash), ..Default::default() }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Deploys the contract that calls the chain extension.
+fn deploy() -> AccountId {
+	let wasm = wat::parse_str(include_str!("../../fixtures/roulette_extension.wat")).unwrap();
+	Contracts::bare_instantiate(
+		alice(),
+		100 * DOLLARS,
+		GAS_LIMIT,
+		None,
+		Code::Upload(wasm.into()),
+		vec![],
+		vec![],
+		false,
+	)
+	.result
+	.unwrap()
+	.account_id
+}
+
+/// Calls a function of the chain extension through `contract`, returning its status code, its
+/// output and the gas that the call consumed.
+fn call_extension(
+	contract: &AccountId,
+	func_id: u16,
+	input: impl Encode,
+) -> (u32, Vec<u8>, Weight) {
+	let mut data = u32::from(func_id).encode();
+	input.encode_to(&mut data);
+
+	let result = Contracts::bare_call(alice(), contract.clone(), 0, GAS_LIMIT, None, data, false);
+	let output = result.result.unwrap().data.to_vec();
+	let status = u32::decode(&mut &output[..4]).unwrap();
+	(status, output[4..].to_vec(), result.gas_consumed)
+}
+
+#[test]
+fn contracts_place_bets_and_list_them() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+
+		let (status, output, _) =
+			call_extension(&contract, PLACE_BET, (NativeAssetId::get(), DOLLARS, Bet::Full(17)));
+		assert_eq!(status, SUCCESS);
+		let bet_id = u64::decode(&mut &output[..]).unwrap();
+
+		let (status, output, _) = call_extension(&contract, ONGOING_BETS, ());
+		assert_eq!(status, SUCCESS);
+		let bets = Vec::<(u32, BetDataOf<Runtime>)>::decode(&mut &output[..]).unwrap();
+		assert_eq!(bets.len(), 1);
+		assert_eq!(bets[0].1.id, bet_id);
+		assert_eq!(bets[0].1.owner, contract);
+	});
+}
+
+#[test]
+fn invalid_bets_return_their_status_code() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+
+		let (status, output, _) =
+			call_extension(&contract, PLACE_BET, (NativeAssetId::get(), DOLLARS, Bet::Full(99)));
+		assert_eq!(status, INVALID_BET);
+		assert!(output.is_empty());
+
+		let (_, output, _) = call_extension(&contract, ONGOING_BETS, ());
+		assert!(Vec::<(u32, BetDataOf<Runtime>)>::decode(&mut &output[..]).unwrap().is_empty());
+	});
+}
+
+#[test]
+fn placing_a_bet_is_charged_for_the_bets_of_its_round() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+		let input = (NativeAssetId::get(), DOLLARS, Bet::Full(17));
+
+		let (status, _, first) = call_extension(&contract, PLACE_BET, input.clone());
+		assert_eq!(status, SUCCESS);
+		for _ in 0..10 {
+			assert!(
+				Lottery::try_place_bet(bob(), NativeAssetId::get(), DOLLARS, Bet::Full(17)).is_ok()
+			);
+		}
+		let (status, _, second) = call_extension(&contract, PLACE_BET, input);
+		assert_eq!(status, SUCCESS);
+
+		// The second bet reads the 11 bets placed before it in the round.
+		let reads = <Runtime as frame_system::Config>::DbWeight::get().reads(11);
+		assert!(second.ref_time() >= first.ref_time() + reads.ref_time());
+	});
+}
+
+#[test]
+fn listing_bets_is_charged_for_the_bets_of_the_contract() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+		let (status, _, empty) = call_extension(&contract, ONGOING_BETS, ());
+		assert_eq!(status, SUCCESS);
+		let limit = <Runtime as frame_system::Config>::DbWeight::get()
+			.reads(2 * u64::from(ONGOING_BETS_LIMIT));
+		assert!(empty.ref_time() < limit.ref_time());
+
+		// Bets of other accounts are not looked at.
+		for _ in 0..10 {
+			assert!(
+				Lottery::try_place_bet(bob(), NativeAssetId::get(), DOLLARS, Bet::Full(17)).is_ok()
+			);
+		}
+		let (_, output, others) = call_extension(&contract, ONGOING_BETS, ());
+		assert!(Vec::<(u32, BetDataOf<Runtime>)>::decode(&mut &output[..]).unwrap().is_empty());
+		assert_eq!(others, empty);
+
+		let input = (NativeAssetId::get(), DOLLARS, Bet::Full(17));
+		for _ in 0..3 {
+			assert_eq!(call_extension(&contract, PLACE_BET, input.clone()).0, SUCCESS);
+		}
+		let (_, output, listed) = call_extension(&contract, ONGOING_BETS, ());
+		assert_eq!(Vec::<(u32, BetDataOf<Runtime>)>::decode(&mut &output[..]).unwrap().len(), 3);
+		let reads = <Runtime as frame_system::Config>::DbWeight::get().reads(6);
+		assert!(listed.ref_time() >= empty.ref_time() + reads.ref_time());
+	});
+}
+
+#[test]
+fn contracts_without_winnings_have_nothing_to_claim() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+
+		let (status, output, _) = call_extension(&contract, CLAIM_WINNINGS, NativeAssetId::get());
+		assert_eq!(status, NOTHING_TO_CLAIM);
+		assert!(output.is_empty());
+	});
+}
+
+#[test]
+fn unknown_functions_trap_the_contract() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+
+		let mut data = 99_u32.encode();
+		data.extend_from_slice(&[0; 8]);
+		let result = Contracts::bare_call(alice(), contract, 0, GAS_LIMIT, None, data, false);
+		assert!(result.result.is_err());
+	});
+}
+
+/// Syndicate contract built by `cargo contract build`.
+const SYNDICATE_WASM: &str =
+	concat!(env!("CARGO_MANIFEST_DIR"), "/../contracts/syndicate/target/ink/syndicate.wasm");
+
+/// Error of the roulette, as the syndicate contract encodes it.
+#[derive(Decode, Debug, PartialEq, Eq)]
+enum RouletteError {
+	NotEnoughBalance,
+	NotEnoughHouseBalance,
+	InvalidBet,
+	BetFailed,
+	NothingToClaim,
+}
+
+/// Error of the syndicate contract, as it encodes it.
+#[derive(Decode, Debug, PartialEq, Eq)]
+enum SyndicateError {
+	NotManager,
+	NotCallback,
+	NothingDeposited,
+	NotEnoughShares,
+	TransferFailed,
+	BetsInPlay,
+	TooManyBetsInPlay,
+	Roulette(RouletteError),
+}
+
+fn charlie() -> AccountId {
+	AccountId::new([3; 32])
+}
+
+/// Selector of an ink! message or constructor.
+fn selector(name: &str) -> Vec<u8> {
+	blake2_256(name.as_bytes())[..4].to_vec()
+}
+
+/// Deploys a syndicate contract managed by `manager`.
+fn deploy_syndicate(manager: AccountId) -> AccountId {
+	let wasm = std::fs::read(SYNDICATE_WASM)
+		.expect("the syndicate contract is built with `cargo contract build`");
+	let mut data = selector("new");
+	NativeAssetId::get().encode_to(&mut data);
+	Contracts::bare_instantiate(
+		manager,
+		0,
+		GAS_LIMIT,
+		None,
+		Code::Upload(wasm.into()),
+		data,
+		vec![],
+		false,
+	)
+	.result
+	.unwrap()
+	.account_id
+}
+
+/// Calls `message` of the syndicate as `caller`, transferring `value`, and decodes what it
+/// returns.
+fn call_syndicate<R: Decode>(
+	contract: &AccountId,
+	caller: AccountId,
+	value: Balance,
+	message: &str,
+	input: impl Encode,
+) -> R {
+	let mut data = selector(message);
+	input.encode_to(&mut data);
+
+	let result =
+		Contracts::bare_call(caller, contract.clone(), value, GAS_LIMIT, None, data, false);
+	let output = result.result.unwrap();
+	assert!(!output.did_revert());
+	R::decode(&mut &output.data[..]).unwrap()
+}
+
+/// Builds blocks up to `n`, running the lottery at the start of every one of them.
+fn run_to_block(n: BlockNumber) {
+	while System::block_number() < n {
+		let block = System::block_number() + 1;
+		System::initialize(&block, &H256::repeat_byte(block as u8), &Default::default());
+		Lottery::on_initialize(block);
+	}
+}
+
+#[test]
+#[ignore = "needs the syndicate contract built with `cargo contract build`"]
+fn syndicates_bet_their_vault_and_claim_its_winnings() {
+	new_test_ext().execute_with(|| {
+		let syndicate = deploy_syndicate(alice());
+		let deposited: Result<Balance, SyndicateError> =
+			call_syndicate(&syndicate, bob(), 1_000 * DOLLARS, "deposit", ());
+		assert_eq!(deposited, Ok(1_000 * DOLLARS));
+
+		// Between them, the bets of the vault cover every pocket, so one of them wins.
+		let bets = [Bet::Color(RouletteColor::Red), Bet::Color(RouletteColor::Black), Bet::Full(0)];
+		let mut bet_ids = Vec::new();
+		for bet in bets.clone() {
+			let placed: Result<u64, SyndicateError> =
+				call_syndicate(&syndicate, alice(), 0, "bet", (DOLLARS, bet));
+			bet_ids.push(placed.unwrap());
+		}
+		let placed: Result<u64, SyndicateError> =
+			call_syndicate(&syndicate, alice(), 0, "bet", (DOLLARS, Bet::Full(99)));
+		assert_eq!(placed, Err(SyndicateError::Roulette(RouletteError::InvalidBet)));
+
+		// The bets of the vault decode as the runtime stores them.
+		let ongoing: Vec<(u32, BetDataOf<Runtime>)> =
+			call_syndicate(&syndicate, alice(), 0, "ongoing_bets", ());
+		assert_eq!(ongoing.len(), bets.len());
+		for (_, bet_data) in ongoing {
+			let index = bet_ids.iter().position(|bet_id| *bet_id == bet_data.id).unwrap();
+			assert_eq!(bet_data.owner, syndicate);
+			assert_eq!(bet_data.amount, DOLLARS);
+			assert_eq!(bet_data.bet, bets[index]);
+		}
+		let withdrawn: Result<Balance, SyndicateError> =
+			call_syndicate(&syndicate, bob(), 0, "withdraw", 1_000 * DOLLARS);
+		assert_eq!(withdrawn, Err(SyndicateError::BetsInPlay));
+
+		run_to_block(BettingPeriod::get() + RandomnessDelay::get() + 3);
+		let ongoing: Vec<(u32, BetDataOf<Runtime>)> =
+			call_syndicate(&syndicate, alice(), 0, "ongoing_bets", ());
+		assert!(ongoing.is_empty());
+
+		// The winnings are credited to the vault until it claims them.
+		let vault = Balances::free_balance(&syndicate);
+		let claimed: Result<Balance, SyndicateError> =
+			call_syndicate(&syndicate, bob(), 0, "claim_winnings", ());
+		let claimed = claimed.unwrap();
+		assert!(claimed > 0);
+		assert_eq!(Balances::free_balance(&syndicate), vault + claimed);
+		let claimed: Result<Balance, SyndicateError> =
+			call_syndicate(&syndicate, bob(), 0, "claim_winnings", ());
+		assert_eq!(claimed, Ok(0));
+
+		// Bob owns every share, so the withdrawal takes the whole vault, winnings included.
+		let vault = Balances::free_balance(&syndicate);
+		let withdrawn: Result<Balance, SyndicateError> =
+			call_syndicate(&syndicate, bob(), 0, "withdraw", 1_000 * DOLLARS);
+		assert_eq!(withdrawn, Ok(vault));
+	});
+}
+
+#[test]
+#[ignore = "needs the syndicate contract built with `cargo contract build`"]
+fn failed_withdrawals_keep_their_shares() {
+	new_test_ext().execute_with(|| {
+		let syndicate = deploy_syndicate(alice());
+		assert!(Balances::transfer(RuntimeOrigin::signed(bob()), charlie().into(), 10 * DOLLARS)
+			.is_ok());
+		let deposited: Result<Balance, SyndicateError> =
+			call_syndicate(&syndicate, charlie(), DOLLARS, "deposit", ());
+		assert_eq!(deposited, Ok(DOLLARS));
+
+		// Once Charlie's account is reaped, a share is worth less than the existential deposit
+		// and cannot be paid to it.
+		assert!(
+			Balances::transfer_all(RuntimeOrigin::signed(charlie()), bob().into(), false).is_ok()
+		);
+		assert_eq!(System::providers(&charlie()), 0);
+		let withdrawn: Result<Balance, SyndicateError> =
+			call_syndicate(&syndicate, charlie(), 0, "withdraw", 1_u128);
+		assert_eq!(withdrawn, Err(SyndicateError::TransferFailed));
+
+		let shares: Balance = call_syndicate(&syndicate, charlie(), 0, "shares_of", charlie());
+		assert_eq!(shares, DOLLARS);
+		let total_shares: Balance = call_syndicate(&syndicate, charlie(), 0, "total_shares", ());
+		assert_eq!(total_shares, DOLLARS);
+	});
+}
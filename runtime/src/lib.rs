@@ -6,6 +6,10 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+mod chain_extension;
+
+pub use chain_extension::{ContractCallback, RouletteExtension};
+
 use pallet_contracts::DefaultContractAccessWeight;
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
@@ -18,7 +22,7 @@ use sp_runtime::{
 	traits::{
 		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::prelude::*;
@@ -41,6 +45,7 @@ pub use frame_support::{
 	StorageValue,
 };
 pub use frame_system::Call as SystemCall;
+use frame_system::EnsureRoot;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
@@ -67,6 +72,9 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of an asset registered in `pallet_assets`.
+pub type AssetId = u32;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
@@ -109,7 +117,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -308,7 +316,7 @@ impl pallet_contracts::Config for Runtime {
 	type CallFilter = frame_support::traits::Nothing;
 	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
 	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
-	type ChainExtension = ();
+	type ChainExtension = RouletteExtension;
 	type Schedule = Schedule;
 	type CallStack = [pallet_contracts::Frame<Self>; 31];
 	type DeletionQueueDepth = DeletionQueueDepth;
@@ -323,11 +331,61 @@ impl pallet_contracts::Config for Runtime {
 	type RuntimeCall = RuntimeCall;
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 100 * DOLLARS;
+	pub const AssetAccountDeposit: Balance = DOLLARS;
+	pub const ApprovalDeposit: Balance = DOLLARS;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: Balance = 10 * DOLLARS;
+	pub const MetadataDepositPerByte: Balance = DOLLARS;
+}
+
+impl pallet_assets::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type AssetAccountDeposit = AssetAccountDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
 impl pallet_sudo::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type RuntimeCall = RuntimeCall;
 }
 
+parameter_types! {
+	pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
+	pub const CouncilMaxProposals: u32 = 100;
+	pub const CouncilMaxMembers: u32 = 100;
+}
+
+/// Council that administers the lottery.
+type CouncilCollective = pallet_collective::Instance1;
+
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+	type RuntimeOrigin = RuntimeOrigin;
+	type Proposal = RuntimeCall;
+	type RuntimeEvent = RuntimeEvent;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+}
+
+/// Two thirds of the council.
+type CouncilMajority =
+	pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 2, 3>;
+
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
@@ -335,14 +393,102 @@ impl pallet_template::Config for Runtime {
 
 parameter_types! {
   pub const RoulettePalletId: PalletId = PalletId(*b"roulette");
+  /// Bets placed in this asset id use the native currency. It must not be created in
+  /// `pallet_assets`.
+  pub const NativeAssetId: AssetId = 0;
+  pub const TicketPrice: Balance = DOLLARS;
+  pub const NumbersPerTicket: u32 = 6;
+  pub const MaxTicketNumber: u32 = 49;
+  pub const DrawPeriod: BlockNumber = DAYS;
+  pub const MaxTicketsPerDraw: u32 = 1_000;
+  pub PrizeTiers: Vec<Perbill> = sp_std::vec![
+	Perbill::from_percent(50),
+	Perbill::from_percent(20),
+	Perbill::from_percent(10),
+  ];
+  pub const JackpotContribution: Perbill = Perbill::from_percent(1);
+  pub const JackpotPayout: Perbill = Perbill::from_percent(90);
+  pub const JackpotStreak: u32 = 2;
+  pub const BettingPeriod: BlockNumber = MINUTES;
+  // Games bound to block hashes take the hash of the block after the one in which they close.
+  pub const RandomnessDelay: BlockNumber = 1;
+  pub const CommitmentDeposit: Balance = 10 * DOLLARS;
+  pub const RevealPeriod: BlockNumber = 10 * MINUTES;
+  pub const MaxCommitments: u32 = 64;
+  pub const MaxBeaconUrlLength: u32 = 256;
+  pub const BeaconUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+  pub const BetPriority: TransactionPriority = TransactionPriority::max_value() / 4;
+  pub const PullPayouts: bool = true;
+  pub const ClaimPeriod: BlockNumber = 30 * DAYS;
+  pub const MaxExpiriesPerBlock: u32 = 256;
+  pub const ReferralShare: Perbill = Perbill::from_percent(10);
+  pub const MaxReferralCodeLength: u32 = 16;
+  pub const MaxVoucherBetKinds: u32 = 6;
+  pub const MaxVoucherExpiries: u32 = 512;
+  pub const MaxScheduledBets: u32 = 256;
+  pub const MaxStrategies: u32 = 256;
+  // 10 ms of execution for every contract called back on settlement.
+  pub const CallbackWeight: Weight = Weight::from_ref_time(10_000_000_000);
+  // Callbacks pay for their weight like transactions do.
+  pub CallbackFee: Balance = Balance::from(CallbackWeight::get().ref_time());
+  // At most 160 ms of every block is spent calling contracts back.
+  pub const MaxCallbacksPerBlock: u32 = 16;
+  pub const MaxRoundsPerBlock: u32 = 8;
+  pub const MaxBetsPerBlock: u32 = 512;
 }
 
 impl pallet_lottery::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type Balance = u128;
-	type LotteryRandomness = RandomnessCollectiveFlip;
+	type AssetId = AssetId;
+	type LotteryRandomness = pallet_lottery::BeaconRandomness<Runtime>;
+	type Game = traits::Roulette;
 	type Currency = Balances;
+	type Assets = Assets;
+	type NativeAssetId = NativeAssetId;
 	type PalletId = RoulettePalletId;
+	type TicketPrice = TicketPrice;
+	type NumbersPerTicket = NumbersPerTicket;
+	type MaxTicketNumber = MaxTicketNumber;
+	type DrawPeriod = DrawPeriod;
+	type MaxTicketsPerDraw = MaxTicketsPerDraw;
+	type PrizeTiers = PrizeTiers;
+	type JackpotContribution = JackpotContribution;
+	type JackpotPayout = JackpotPayout;
+	type JackpotStreak = JackpotStreak;
+	type BettingPeriod = BettingPeriod;
+	type RandomnessDelay = RandomnessDelay;
+	type CommitmentDeposit = CommitmentDeposit;
+	type RevealPeriod = RevealPeriod;
+	type MaxCommitments = MaxCommitments;
+	type UnixTime = Timestamp;
+	type MaxBeaconUrlLength = MaxBeaconUrlLength;
+	type BeaconUnsignedPriority = BeaconUnsignedPriority;
+	type BetPriority = BetPriority;
+	type PullPayouts = PullPayouts;
+	type ClaimPeriod = ClaimPeriod;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+	type ReferralShare = ReferralShare;
+	type MaxReferralCodeLength = MaxReferralCodeLength;
+	type AdminOrigin = CouncilMajority;
+	type MaxVoucherBetKinds = MaxVoucherBetKinds;
+	type MaxVoucherExpiries = MaxVoucherExpiries;
+	type MaxScheduledBets = MaxScheduledBets;
+	type MaxStrategies = MaxStrategies;
+	type BetCallback = ContractCallback;
+	type CallbackWeight = CallbackWeight;
+	type CallbackFee = CallbackFee;
+	type MaxCallbacksPerBlock = MaxCallbacksPerBlock;
+	type MaxRoundsPerBlock = MaxRoundsPerBlock;
+	type MaxBetsPerBlock = MaxBetsPerBlock;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = UncheckedExtrinsic;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -359,9 +505,11 @@ construct_runtime!(
 		Aura: pallet_aura,
 		Grandpa: pallet_grandpa,
 		Balances: pallet_balances,
+		Assets: pallet_assets,
 		Vesting: pallet_vesting,
 		TransactionPayment: pallet_transaction_payment,
 		Sudo: pallet_sudo,
+		Council: pallet_collective::<Instance1>,
 		Contracts: pallet_contracts,
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template,
@@ -385,6 +533,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_lottery::CheckBet<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -392,6 +541,8 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Storage migrations run when the runtime is upgraded.
+pub type Migrations = (pallet_lottery::migrations::v1::MigrateToV1<Runtime, Balances>,);
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -399,6 +550,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -411,6 +563,7 @@ mod benches {
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_assets, Assets]
 		[pallet_vesting, Vesting]
 		[pallet_timestamp, Timestamp]
 		[pallet_template, TemplateModule]
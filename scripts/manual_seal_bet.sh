@@ -0,0 +1,58 @@
+#!/usr/bin/env bash
+# This script is meant to be run on Unix/Linux based systems
+#
+# Starts a `roulette-dev` node that seals blocks manually, places a bet as Alice, seals blocks
+# until its round is played and checks that the bet settled. Needs a built node, curl, jq and npx
+# (for `@polkadot/api-cli`).
+set -euo pipefail
+
+cd $(dirname ${BASH_SOURCE[0]})/..
+
+NODE=${NODE:-./target/release/node-template}
+RPC_PORT=${RPC_PORT:-9933}
+WS_PORT=${WS_PORT:-9944}
+# The betting window of a round is a minute of 6 second blocks.
+MAX_BLOCKS=${MAX_BLOCKS:-20}
+
+rpc() {
+	curl -sf -H "Content-Type: application/json" \
+		-d "{\"id\": 1, \"jsonrpc\": \"2.0\", \"method\": \"$1\", \"params\": $2}" \
+		http://127.0.0.1:$RPC_PORT
+}
+
+seal() {
+	rpc engine_createBlock '[true, true, null]' > /dev/null
+}
+
+echo "*** Starting a node sealing blocks manually ***"
+$NODE --chain roulette-dev --alice --tmp --sealing manual --enable-offchain-indexing true \
+	--rpc-port $RPC_PORT --ws-port $WS_PORT > /tmp/manual_seal_bet.log 2>&1 &
+NODE_PID=$!
+trap "kill $NODE_PID" EXIT
+
+until rpc system_health '[]' > /dev/null; do
+	sleep 1
+done
+
+echo "*** Placing a bet of one dollar on 17 ***"
+npx @polkadot/api-cli --ws ws://127.0.0.1:$WS_PORT --seed //Alice \
+	tx.lottery.placeBet 0 100000000000000 '{"Full": 17}' > /dev/null &
+BET_PID=$!
+until ! kill -0 $BET_PID 2> /dev/null; do
+	sleep 1
+	seal
+done
+wait $BET_PID
+
+echo "*** Sealing blocks until the bet settles ***"
+for _ in $(seq $MAX_BLOCKS); do
+	seal
+	RECORD=$(rpc lottery_settledBet '[0]' | jq -c '.result')
+	if [ "$RECORD" != "null" ]; then
+		echo "Bet settled: $RECORD"
+		exit 0
+	fi
+done
+
+echo "The bet did not settle within $MAX_BLOCKS blocks" >&2
+exit 1